@@ -19,11 +19,34 @@
 #include <stdlib.h>
 #include <string.h>
 
+/* Rec. 601 luma weights */
+static const float LUMA[3] = { 0.299f, 0.587f, 0.114f };
+
+/* Read a float from the environment, clamped to [min, max]. Anything that
+ * is not a number is ignored with a warning, keeping the default. */
+static float env_float(const char *name, float def, float min, float max)
+{
+    const char *s = getenv(name);
+    if (!s || s[0] == '\0') return def;
+    char *end;
+    float v = strtof(s, &end);
+    while (*end == ' ' || *end == '\t' || *end == '\n') end++;
+    if (end == s || *end != '\0' || v != v) {
+        fprintf(stderr, "Ignoring invalid %s '%s' (want a number)\n", name, s);
+        return def;
+    }
+    if (v < min) v = min;
+    if (v > max) v = max;
+    return v;
+}
+
 struct display_config read_display_config(void)
 {
     struct display_config cfg = {
         .fade_duration = DEFAULT_FADE_DURATION,
+        .hold_duration = DEFAULT_HOLD_DURATION,
         .skip_frames = DEFAULT_SKIP_FRAMES,
+        .night_clock_brightness = DEFAULT_NIGHT_CLOCK_BRIGHTNESS,
     };
 
     const char *env_fade = getenv("PHOTO_FRAME_FADE_DURATION");
@@ -38,10 +61,296 @@ struct display_config read_display_config(void)
         if (cfg.skip_frames < 0) cfg.skip_frames = 0;
     }
 
-    printf("Display config: fade=%.1fs skip=%d\n", cfg.fade_duration, cfg.skip_frames);
+    cfg.hold_duration = env_float("PHOTO_FRAME_HOLD_DURATION", DEFAULT_HOLD_DURATION,
+                                  1.0f, 86400.0f);
+    const char *env_hold = getenv("PHOTO_FRAME_HOLD_SCHEDULE");
+    if (env_hold && env_hold[0] != '\0') {
+        if (!parse_hold_schedule(env_hold, &cfg.hold_schedule)) {
+            fprintf(stderr, "Ignoring invalid PHOTO_FRAME_HOLD_SCHEDULE '%s' "
+                    "(want HH:MM-HH:MM=SECONDS[,...])\n", env_hold);
+        }
+    }
+
+    const char *env_quiet = getenv("PHOTO_FRAME_QUIET_HOURS");
+    if (env_quiet && env_quiet[0] != '\0') {
+        if (!parse_time_range(env_quiet, &cfg.quiet_hours)) {
+            fprintf(stderr, "Ignoring invalid PHOTO_FRAME_QUIET_HOURS '%s' (want HH:MM-HH:MM)\n",
+                    env_quiet);
+        }
+    }
+
+    const char *env_clock = getenv("PHOTO_FRAME_NIGHT_CLOCK");
+    if (env_clock && env_clock[0] != '\0') {
+        cfg.night_clock = strtol(env_clock, NULL, 10) != 0;
+    }
+
+    const char *env_clock_bright = getenv("PHOTO_FRAME_NIGHT_CLOCK_BRIGHTNESS");
+    if (env_clock_bright && env_clock_bright[0] != '\0') {
+        cfg.night_clock_brightness = strtof(env_clock_bright, NULL);
+        if (cfg.night_clock_brightness < 0.0f) cfg.night_clock_brightness = 0.0f;
+        if (cfg.night_clock_brightness > 1.0f) cfg.night_clock_brightness = 1.0f;
+    }
+
+    cfg.color.brightness = env_float("PHOTO_FRAME_BRIGHTNESS", DEFAULT_BRIGHTNESS, -1.0f, 1.0f);
+    cfg.color.contrast = env_float("PHOTO_FRAME_CONTRAST", DEFAULT_CONTRAST, 0.0f, 4.0f);
+    cfg.color.saturation = env_float("PHOTO_FRAME_SATURATION", DEFAULT_SATURATION, 0.0f, 4.0f);
+    cfg.color.gamma = env_float("PHOTO_FRAME_GAMMA", DEFAULT_GAMMA, 0.1f, 10.0f);
+    cfg.color.vignette = env_float("PHOTO_FRAME_VIGNETTE", 0.0f, 0.0f, 1.0f);
+
+    cfg.watermark.opacity = env_float("PHOTO_FRAME_WATERMARK_OPACITY",
+                                      DEFAULT_WATERMARK_OPACITY, 0.0f, 1.0f);
+    cfg.watermark.size = env_float("PHOTO_FRAME_WATERMARK_SIZE",
+                                   DEFAULT_WATERMARK_SIZE, 0.01f, 1.0f);
+    const char *env_wm = getenv("PHOTO_FRAME_WATERMARK");
+    if (env_wm && env_wm[0] != '\0') {
+        cfg.watermark.path = env_wm;
+    }
+    const char *env_wm_pos = getenv("PHOTO_FRAME_WATERMARK_POSITION");
+    if (env_wm_pos && env_wm_pos[0] != '\0') {
+        if (!parse_watermark_position(env_wm_pos, &cfg.watermark.position)) {
+            fprintf(stderr, "Ignoring unknown PHOTO_FRAME_WATERMARK_POSITION '%s'\n", env_wm_pos);
+        }
+    }
+
+    const char *env_filter = getenv("PHOTO_FRAME_FILTER");
+    if (env_filter && env_filter[0] != '\0') {
+        if (!parse_photo_filter(env_filter, &cfg.color.filter)) {
+            fprintf(stderr, "Ignoring unknown PHOTO_FRAME_FILTER '%s' (want none, grayscale, or sepia)\n",
+                    env_filter);
+        }
+    }
+
+    printf("Display config: fade=%.1fs hold=%.1fs skip=%d\n", cfg.fade_duration,
+           cfg.hold_duration, cfg.skip_frames);
+    for (int i = 0; i < cfg.hold_schedule.count; ++i) {
+        const struct time_range *r = &cfg.hold_schedule.rules[i].range;
+        printf("Hold %02d:%02d-%02d:%02d: %.1fs\n",
+               r->start_min / 60, r->start_min % 60, r->end_min / 60, r->end_min % 60,
+               cfg.hold_schedule.rules[i].seconds);
+    }
+    if (!color_adjust_is_identity(&cfg.color)) {
+        static const char *filter_names[] = { "none", "grayscale", "sepia" };
+        printf("Color: brightness=%.2f contrast=%.2f saturation=%.2f gamma=%.2f filter=%s vignette=%.2f\n",
+               cfg.color.brightness, cfg.color.contrast,
+               cfg.color.saturation, cfg.color.gamma,
+               filter_names[cfg.color.filter], cfg.color.vignette);
+    }
+    if (cfg.quiet_hours.enabled) {
+        printf("Quiet hours: %02d:%02d-%02d:%02d%s\n",
+               cfg.quiet_hours.start_min / 60, cfg.quiet_hours.start_min % 60,
+               cfg.quiet_hours.end_min / 60, cfg.quiet_hours.end_min % 60,
+               cfg.night_clock ? " (night clock)" : "");
+    }
+    if (cfg.watermark.path) {
+        printf("Watermark: %s (opacity %.2f, size %.2f)\n", cfg.watermark.path,
+               cfg.watermark.opacity, cfg.watermark.size);
+    }
     return cfg;
 }
 
+static int parse_hhmm(const char *s, int *minutes, const char **end)
+{
+    char *p;
+    long h = strtol(s, &p, 10);
+    if (p == s || *p != ':') return 0;
+    const char *m_start = p + 1;
+    long m = strtol(m_start, &p, 10);
+    if (p - m_start != 2) return 0;
+    if (h < 0 || h > 24 || m < 0 || m > 59 || (h == 24 && m != 0)) return 0;
+    *minutes = (int)(h * 60 + m);
+    *end = p;
+    return 1;
+}
+
+int parse_time_range(const char *s, struct time_range *out)
+{
+    int start, end;
+    const char *p;
+
+    out->enabled = 0;
+    if (!parse_hhmm(s, &start, &p) || *p != '-') return 0;
+    if (!parse_hhmm(p + 1, &end, &p) || *p != '\0') return 0;
+    if (start >= 24 * 60 || start == end) return 0;
+
+    out->start_min = start;
+    out->end_min = end;
+    out->enabled = 1;
+    return 1;
+}
+
+int parse_hold_schedule(const char *s, struct hold_schedule *out)
+{
+    char buf[256];
+    out->count = 0;
+    if (strlen(s) >= sizeof(buf)) return 0;
+    strcpy(buf, s);
+
+    char *save = NULL;
+    for (char *rule = strtok_r(buf, ",", &save); rule; rule = strtok_r(NULL, ",", &save)) {
+        char *eq = strchr(rule, '=');
+        if (!eq || out->count == HOLD_SCHEDULE_MAX) goto fail;
+        *eq = '\0';
+
+        char *end;
+        float seconds = strtof(eq + 1, &end);
+        if (end == eq + 1 || *end != '\0' || !(seconds >= 1.0f && seconds <= 86400.0f))
+            goto fail;
+        if (!parse_time_range(rule, &out->rules[out->count].range)) goto fail;
+        out->rules[out->count].seconds = seconds;
+        out->count++;
+    }
+    return out->count > 0;
+
+fail:
+    out->count = 0;
+    return 0;
+}
+
+float hold_duration_at(const struct hold_schedule *s, float def, int minute_of_day)
+{
+    for (int i = 0; i < s->count; ++i) {
+        if (time_in_range(&s->rules[i].range, minute_of_day))
+            return s->rules[i].seconds;
+    }
+    return def;
+}
+
+int time_in_range(const struct time_range *r, int minute_of_day)
+{
+    if (!r->enabled) return 0;
+    if (r->start_min < r->end_min)
+        return minute_of_day >= r->start_min && minute_of_day < r->end_min;
+    /* Wraps past midnight, e.g. 22:00-07:00 */
+    return minute_of_day >= r->start_min || minute_of_day < r->end_min;
+}
+
+int clock_is_sane(time_t now, time_t reference)
+{
+    return now >= MIN_SANE_TIME && now >= reference;
+}
+
+int seven_segment_mask(int digit)
+{
+    static const int masks[10] = {
+        0x3F, 0x06, 0x5B, 0x4F, 0x66, 0x6D, 0x7D, 0x07, 0x7F, 0x6F
+    };
+    if (digit < 0 || digit > 9) return 0;
+    return masks[digit];
+}
+
+static void put_rect(float *r, float x0, float y0, float x1, float y1)
+{
+    r[0] = x0; r[1] = y0; r[2] = x1; r[3] = y1;
+}
+
+int build_clock_rects(int hour, int minute, float height, float screen_aspect,
+                      float *rects)
+{
+    /* Sizes in NDC; x values are divided by the aspect so segments keep
+     * their proportions on any screen. */
+    float dw  = height * 0.5f / screen_aspect;   /* digit width */
+    float t   = height * 0.1f;                   /* stroke thickness (y) */
+    float tx  = t / screen_aspect;               /* stroke thickness (x) */
+    float gap = dw * 0.3f;                       /* between digits */
+    float cgap = dw * 0.4f;                      /* either side of the colon */
+
+    float total = 4.0f * dw + 2.0f * gap + 2.0f * cgap + tx;
+    float y0 = -height / 2.0f, y1 = height / 2.0f, ym = 0.0f;
+    int digits[4] = { hour / 10, hour % 10, minute / 10, minute % 10 };
+    int n = 0;
+
+    float x = -total / 2.0f;
+    for (int i = 0; i < 4; ++i) {
+        int mask = seven_segment_mask(digits[i]);
+        float *r;
+        if (mask & 0x01) { r = rects + 4 * n++; put_rect(r, x, y1 - t, x + dw, y1); }
+        if (mask & 0x02) { r = rects + 4 * n++; put_rect(r, x + dw - tx, ym, x + dw, y1); }
+        if (mask & 0x04) { r = rects + 4 * n++; put_rect(r, x + dw - tx, y0, x + dw, ym); }
+        if (mask & 0x08) { r = rects + 4 * n++; put_rect(r, x, y0, x + dw, y0 + t); }
+        if (mask & 0x10) { r = rects + 4 * n++; put_rect(r, x, y0, x + tx, ym); }
+        if (mask & 0x20) { r = rects + 4 * n++; put_rect(r, x, ym, x + tx, y1); }
+        if (mask & 0x40) { r = rects + 4 * n++; put_rect(r, x, ym - t / 2.0f, x + dw, ym + t / 2.0f); }
+
+        x += dw;
+        if (i == 0 || i == 2) {
+            x += gap;
+        } else if (i == 1) {
+            /* Colon */
+            x += cgap;
+            float cy = height * 0.15f;
+            put_rect(rects + 4 * n++, x, cy, x + tx, cy + t);
+            put_rect(rects + 4 * n++, x, -cy - t, x + tx, -cy);
+            x += tx + cgap;
+        }
+    }
+    return n;
+}
+
+int color_adjust_is_identity(const struct color_adjust *c)
+{
+    return c->brightness == DEFAULT_BRIGHTNESS && c->contrast == DEFAULT_CONTRAST &&
+           c->saturation == DEFAULT_SATURATION && c->gamma == DEFAULT_GAMMA &&
+           c->filter == FILTER_NONE && c->vignette == 0.0f;
+}
+
+int parse_photo_filter(const char *s, enum photo_filter *out)
+{
+    if (strcmp(s, "none") == 0) {
+        *out = FILTER_NONE;
+    } else if (strcmp(s, "grayscale") == 0 || strcmp(s, "greyscale") == 0) {
+        *out = FILTER_GRAYSCALE;
+    } else if (strcmp(s, "sepia") == 0) {
+        *out = FILTER_SEPIA;
+    } else {
+        return 0;
+    }
+    return 1;
+}
+
+/* Saturation matrix, row-major: each output channel mixes toward luma. */
+static void saturation_matrix(float saturation, float m[3][3])
+{
+    for (int row = 0; row < 3; ++row) {
+        for (int col = 0; col < 3; ++col) {
+            m[row][col] = (1.0f - saturation) * LUMA[col] +
+                          (row == col ? saturation : 0.0f);
+        }
+    }
+}
+
+void build_color_matrix(const struct color_adjust *c, float *m)
+{
+    /* The common sepia tone matrix, row-major */
+    static const float sepia[3][3] = {
+        { 0.393f, 0.769f, 0.189f },
+        { 0.349f, 0.686f, 0.168f },
+        { 0.272f, 0.534f, 0.131f },
+    };
+    float sat[3][3], filter[3][3];
+
+    saturation_matrix(c->saturation, sat);
+    switch (c->filter) {
+    case FILTER_GRAYSCALE:
+        saturation_matrix(0.0f, filter);
+        break;
+    case FILTER_SEPIA:
+        memcpy(filter, sepia, sizeof(filter));
+        break;
+    default:
+        saturation_matrix(1.0f, filter);
+        break;
+    }
+
+    /* m = filter * sat, stored column-major */
+    for (int row = 0; row < 3; ++row) {
+        for (int col = 0; col < 3; ++col) {
+            float sum = 0.0f;
+            for (int k = 0; k < 3; ++k) sum += filter[row][k] * sat[k][col];
+            m[col * 3 + row] = sum;
+        }
+    }
+}
+
 void build_quad(float img_aspect, float screen_aspect, float *v)
 {
     float x0, x1, y0, y1;
@@ -60,6 +369,49 @@ void build_quad(float img_aspect, float screen_aspect, float *v)
     v[12] = x1; v[13] = y1; v[14] = 1.0f; v[15] = 0.0f;
 }
 
+int parse_watermark_position(const char *s, enum watermark_position *out)
+{
+    static const char *names[] = { "bottom-right", "bottom-left", "top-right", "top-left" };
+    for (int i = 0; i < 4; ++i) {
+        if (strcmp(s, names[i]) == 0) {
+            *out = (enum watermark_position)i;
+            return 1;
+        }
+    }
+    return 0;
+}
+
+void build_watermark_quad(float img_aspect, float screen_aspect, float size,
+                          enum watermark_position position, float *v)
+{
+    /* NDC spans 2 units, so a fraction f of the screen is 2f */
+    float h = 2.0f * size;
+    float w = h * img_aspect / screen_aspect;
+    float margin_y = 2.0f * 0.03f;
+    float margin_x = margin_y / screen_aspect;
+
+    int right = position == WATERMARK_BOTTOM_RIGHT || position == WATERMARK_TOP_RIGHT;
+    int top = position == WATERMARK_TOP_RIGHT || position == WATERMARK_TOP_LEFT;
+    float x0 = right ? 1.0f - margin_x - w : -1.0f + margin_x;
+    float y0 = top ? 1.0f - margin_y - h : -1.0f + margin_y;
+    float x1 = x0 + w, y1 = y0 + h;
+
+    v[0]  = x0; v[1]  = y0; v[2]  = 0.0f; v[3]  = 1.0f;
+    v[4]  = x1; v[5]  = y0; v[6]  = 1.0f; v[7]  = 1.0f;
+    v[8]  = x0; v[9]  = y1; v[10] = 0.0f; v[11] = 0.0f;
+    v[12] = x1; v[13] = y1; v[14] = 1.0f; v[15] = 0.0f;
+}
+
+void premultiply_alpha(unsigned char *px, size_t n)
+{
+    for (size_t i = 0; i < n; ++i) {
+        unsigned a = px[4 * i + 3];
+        for (int c = 0; c < 3; ++c) {
+            px[4 * i + c] = (unsigned char)((px[4 * i + c] * a + 127) / 255);
+        }
+    }
+}
+
 int select_image_destination(int slot0_occupied, int slot1_occupied, int has_pending)
 {
     if (!slot0_occupied) return 0;
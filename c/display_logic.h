@@ -18,18 +18,152 @@
 #define DISPLAY_LOGIC_H
 
 #include <stddef.h>
+#include <time.h>
 
 #define DEFAULT_FADE_DURATION  1.5f
+#define DEFAULT_HOLD_DURATION  5.0f
 #define DEFAULT_SKIP_FRAMES    0
+#define DEFAULT_NIGHT_CLOCK_BRIGHTNESS 0.25f
+#define DEFAULT_BRIGHTNESS     0.0f
+#define DEFAULT_CONTRAST       1.0f
+#define DEFAULT_SATURATION     1.0f
+#define DEFAULT_GAMMA          1.0f
+#define DEFAULT_WATERMARK_OPACITY 0.8f
+#define DEFAULT_WATERMARK_SIZE    0.1f
+
+/* 2024-01-01 00:00 UTC. A Pi without an RTC boots at 1970 (or at the time
+ * fake-hwclock saved) until NTP syncs; anything earlier than this is wrong. */
+#define MIN_SANE_TIME          ((time_t)1704067200)
+
+/* Most time ranges accepted in PHOTO_FRAME_HOLD_SCHEDULE. */
+#define HOLD_SCHEDULE_MAX      8
+
+/* Enough rectangles for "88:88": four digits of seven segments plus a colon. */
+#define CLOCK_MAX_RECTS        (4 * 7 + 2)
+
+/* A daily time window in minutes since local midnight. end is exclusive and
+ * may be smaller than start for windows that wrap past midnight. */
+struct time_range {
+    int enabled;
+    int start_min;
+    int end_min;
+};
+
+/* Hold durations for parts of the day. The first rule whose range contains
+ * the current minute wins; outside all of them the default applies. */
+struct hold_schedule {
+    int count;
+    struct {
+        struct time_range range;
+        float seconds;
+    } rules[HOLD_SCHEDULE_MAX];
+};
+
+enum photo_filter {
+    FILTER_NONE,
+    FILTER_GRAYSCALE,
+    FILTER_SEPIA,
+};
+
+/* Per-panel color correction, applied in the fragment shader.
+ * brightness is added (-1..1), contrast scales around mid-gray, saturation
+ * scales away from luma, and gamma > 1 brightens midtones. filter is applied
+ * after saturation, and vignette (0..1) darkens the corners of each photo. */
+struct color_adjust {
+    float brightness;
+    float contrast;
+    float saturation;
+    float gamma;
+    enum photo_filter filter;
+    float vignette;
+};
+
+enum watermark_position {
+    WATERMARK_BOTTOM_RIGHT,
+    WATERMARK_BOTTOM_LEFT,
+    WATERMARK_TOP_RIGHT,
+    WATERMARK_TOP_LEFT,
+};
+
+/* A logo drawn over every slide. path is NULL when disabled; size is the
+ * logo height as a fraction of the screen height. */
+struct watermark {
+    const char *path;
+    enum watermark_position position;
+    float opacity;
+    float size;
+};
 
 struct display_config {
     float fade_duration;
+    float hold_duration;
+    struct hold_schedule hold_schedule;
     int skip_frames;
+    struct time_range quiet_hours;
+    int night_clock;
+    float night_clock_brightness;
+    struct color_adjust color;
+    struct watermark watermark;
 };
 
 struct display_config read_display_config(void);
+
+/* Parse "HH:MM-HH:MM" (end may be 24:00). Returns 1 on success, 0 if malformed. */
+int parse_time_range(const char *s, struct time_range *out);
+
+/* Returns 1 if minute_of_day (0-1439) falls inside an enabled range. */
+int time_in_range(const struct time_range *r, int minute_of_day);
+
+/* Returns 1 if now looks like the real time: not before MIN_SANE_TIME, and
+ * not before reference (the mtime of a file known to be older than now, or
+ * 0 for none). */
+int clock_is_sane(time_t now, time_t reference);
+
+/* Parse "HH:MM-HH:MM=SECONDS[,...]", e.g. "18:00-23:00=60,06:00-08:00=20".
+ * Returns 1 on success, 0 if any rule is malformed (out->count is then 0). */
+int parse_hold_schedule(const char *s, struct hold_schedule *out);
+
+/* Hold duration at minute_of_day (0-1439): the first matching rule's, else
+ * def. */
+float hold_duration_at(const struct hold_schedule *s, float def, int minute_of_day);
+
+/* Lit segments of a seven-segment digit, bit 0 = top (a) through bit 6 =
+ * middle (g). Returns 0 for anything outside 0-9. */
+int seven_segment_mask(int digit);
+
+/* Lay out HH:MM as solid rectangles (x0, y0, x1, y1 in NDC) centered on the
+ * screen. height is the digit height in NDC. Returns the rectangle count,
+ * at most CLOCK_MAX_RECTS. */
+int build_clock_rects(int hour, int minute, float height, float screen_aspect,
+                      float *rects);
+
+/* Returns 1 if c leaves every pixel unchanged. */
+int color_adjust_is_identity(const struct color_adjust *c);
+
+/* Parse "none", "grayscale" (or "greyscale"), or "sepia". Returns 1 on
+ * success, 0 if unknown. */
+int parse_photo_filter(const char *s, enum photo_filter *out);
+
+/* 3x3 color matrix for c's saturation and filter, in column-major order (as
+ * glUniformMatrix3fv takes it). Saturation 0 = grayscale, 1 = unchanged;
+ * luma is preserved. */
+void build_color_matrix(const struct color_adjust *c, float *m);
+
 void build_quad(float img_aspect, float screen_aspect, float *v);
 
+/* Parse "bottom-right", "bottom-left", "top-right", or "top-left". Returns 1
+ * on success, 0 if unknown. */
+int parse_watermark_position(const char *s, enum watermark_position *out);
+
+/* Quad for a logo of img_aspect, size (fraction of screen height) tall, in
+ * the given corner with a small margin. Same vertex layout as build_quad. */
+void build_watermark_quad(float img_aspect, float screen_aspect, float size,
+                          enum watermark_position position, float *v);
+
+/* Multiply RGB by alpha in place for n RGBA pixels, for blending with
+ * GL_ONE, GL_ONE_MINUS_SRC_ALPHA. */
+void premultiply_alpha(unsigned char *px, size_t n);
+
 /* Returns: 0 = slot 0, 1 = slot 1, 2 = pending, 3 = drop */
 int select_image_destination(int slot0_occupied, int slot1_occupied, int has_pending);
 
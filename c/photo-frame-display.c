@@ -47,6 +47,7 @@
 #include <fcntl.h>
 #include <unistd.h>
 #include <errno.h>
+#include <limits.h>
 #include <time.h>
 #include <sys/epoll.h>
 #include <sys/socket.h>
@@ -72,7 +73,8 @@
 #endif
 
 #define SOCKET_PATH            "/run/photo-frame/photo-frame.sock"
-#define HOLD_DURATION_SEC      5.0f
+#define QUIET_CHECK_INTERVAL_MS 30000
+#define NIGHT_CLOCK_HEIGHT     0.5f   /* digit height in NDC (quarter of the screen) */
 
 #define CHECK(cond, ...) do { \
     if (!(cond)) { \
@@ -118,6 +120,11 @@ struct app_state {
     uint32_t             crtc_id;
     drmModeCrtc         *saved_crtc;
     GLint                u_alpha_loc;
+    GLint                u_color_loc;     /* -1 unless color adjustment is on */
+    GLint                u_brightness_loc;
+    GLint                u_contrast_loc;
+    GLint                u_inv_gamma_loc;
+    GLint                u_vignette_loc;
 
     /* Images */
     struct image_slot    slots[2];
@@ -148,7 +155,8 @@ struct app_state {
     enum {
         PHASE_WAITING,
         PHASE_HOLDING,
-        PHASE_FADING
+        PHASE_FADING,
+        PHASE_QUIET
     } phase;
     struct timespec      hold_deadline;
     int                  hold_complete;
@@ -159,9 +167,30 @@ struct app_state {
 
     /* Configurable fade */
     float                fade_duration;
+    float                hold_duration;
+    struct hold_schedule hold_schedule;
     int                  skip_frames;
     int                  frame_counter;
 
+    /* Quiet hours (screen blanked, slideshow paused) */
+    struct time_range    quiet_hours;
+    int                  quiet_leaving;
+    int                  night_clock;
+    float                night_clock_brightness;
+    GLuint               clock_tex;      /* 1x1 red texel for clock segments */
+    int                  clock_minute;   /* minute of day currently drawn */
+    time_t               clock_reference; /* mtime of our binary */
+    int                  clock_sane;
+    int                  clock_warned;
+
+    /* Panel color correction */
+    struct color_adjust  color;
+
+    /* Logo drawn over every slide */
+    struct watermark     watermark;
+    GLuint               watermark_tex;
+    int                  watermark_w, watermark_h;
+
     /* Graceful shutdown */
     volatile sig_atomic_t running;
 } g;
@@ -375,6 +404,82 @@ static void handle_socket_data(void)
 /* Fade / render                                                              */
 /* -------------------------------------------------------------------------- */
 
+/* Load the color correction uniforms, or neutral values for drawing that
+ * must not be corrected (night clock, watermark). No-op with the plain shader. */
+static void set_color_uniforms(int neutral)
+{
+    if (g.u_color_loc < 0) return;
+
+    static const struct color_adjust identity = {
+        DEFAULT_BRIGHTNESS, DEFAULT_CONTRAST, DEFAULT_SATURATION, DEFAULT_GAMMA,
+        FILTER_NONE, 0.0f
+    };
+    const struct color_adjust *c = neutral ? &identity : &g.color;
+    GLfloat m[9];
+    build_color_matrix(c, m);
+    glUniformMatrix3fv(g.u_color_loc, 1, GL_FALSE, m);
+    glUniform1f(g.u_brightness_loc, c->brightness);
+    glUniform1f(g.u_contrast_loc, c->contrast);
+    glUniform1f(g.u_inv_gamma_loc, 1.0f / c->gamma);
+    glUniform1f(g.u_vignette_loc, c->vignette);
+}
+
+/* Load the watermark PNG into its own texture. On failure the frame runs
+ * without one rather than refusing to start. */
+static void load_watermark(void)
+{
+    if (!g.watermark.path) return;
+
+    int w, h, ch;
+    unsigned char *data = stbi_load(g.watermark.path, &w, &h, &ch, 4);
+    if (!data) {
+        fprintf(stderr, "Failed to load watermark %s: %s\n",
+                g.watermark.path, stbi_failure_reason());
+        g.watermark.path = NULL;
+        return;
+    }
+    premultiply_alpha(data, (size_t)w * (size_t)h);
+
+    glGenTextures(1, &g.watermark_tex);
+    glBindTexture(GL_TEXTURE_2D, g.watermark_tex);
+    glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_MIN_FILTER, GL_LINEAR);
+    glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_MAG_FILTER, GL_LINEAR);
+    glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_WRAP_S, GL_CLAMP_TO_EDGE);
+    glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_WRAP_T, GL_CLAMP_TO_EDGE);
+    glTexImage2D(GL_TEXTURE_2D, 0, GL_RGBA, w, h, 0,
+                 GL_RGBA, GL_UNSIGNED_BYTE, data);
+    stbi_image_free(data);
+
+    g.watermark_w = w;
+    g.watermark_h = h;
+    printf("Loaded watermark %s (%dx%d)\n", g.watermark.path, w, h);
+}
+
+/* Draw the watermark on top of whatever is in the back buffer. It does not
+ * take part in fades and is not color corrected. */
+static void draw_watermark(void)
+{
+    if (!g.watermark.path) return;
+
+    GLfloat verts[16];
+    build_watermark_quad((float)g.watermark_w / (float)g.watermark_h,
+                         g.screen_aspect, g.watermark.size,
+                         g.watermark.position, verts);
+    glBufferSubData(GL_ARRAY_BUFFER, 0, sizeof(verts), verts);
+    glBindTexture(GL_TEXTURE_2D, g.watermark_tex);
+    glUniform1f(g.u_alpha_loc, g.watermark.opacity);
+    set_color_uniforms(1);
+
+    /* The texture is premultiplied, and the shader scales all four
+     * channels by the opacity */
+    glEnable(GL_BLEND);
+    glBlendFunc(GL_ONE, GL_ONE_MINUS_SRC_ALPHA);
+    glDrawArrays(GL_TRIANGLE_STRIP, 0, 4);
+    glDisable(GL_BLEND);
+
+    set_color_uniforms(0);
+}
+
 static void render_frame(float mix, int from_slot, int to_slot)
 {
     glClearColor(0.0f, 0.0f, 0.0f, 1.0f);
@@ -402,6 +507,7 @@ static void render_frame(float mix, int from_slot, int to_slot)
     glDrawArrays(GL_TRIANGLE_STRIP, 0, 4);
 
     glDisable(GL_BLEND);
+    draw_watermark();
 }
 
 static void request_page_flip(void)
@@ -428,6 +534,122 @@ static void request_page_flip(void)
     CHECK(ret == 0, "drmModePageFlip");
 }
 
+static void promote_pending_fb(void)
+{
+    if (g.pending_fb.bo) {
+        if (g.scanout_fb.bo) {
+            drmModeRmFB(g.drm_fd, g.scanout_fb.fb_id);
+            gbm_surface_release_buffer(g.gbm_surf, g.scanout_fb.bo);
+        }
+        g.scanout_fb = g.pending_fb;
+        g.pending_fb.bo    = NULL;
+        g.pending_fb.fb_id = 0;
+    }
+}
+
+/* Quiet hours and the night clock use local time. localtime_r() is not
+ * required to pick up TZ changes by itself, so load the zone once here. */
+static void init_timezone(void)
+{
+    tzset();
+    const char *tz = getenv("TZ");
+    if (tz && tz[0] != '\0') {
+        const char *name = tz[0] == ':' ? tz + 1 : tz;
+        char zone_path[PATH_MAX];
+        snprintf(zone_path, sizeof(zone_path), "/usr/share/zoneinfo/%s", name);
+        /* glibc silently falls back to UTC for unknown zone names */
+        if (strchr(name, '/') && name[0] != '/' && access(zone_path, R_OK) != 0) {
+            fprintf(stderr, "Warning: time zone '%s' not found in /usr/share/zoneinfo\n", name);
+        }
+    }
+    printf("Time zone: %s/%s (%s)\n", tzname[0], tzname[1],
+           tz && tz[0] != '\0' ? tz : "system default");
+
+    /* The installed binary is older than any real "now" */
+    struct stat st;
+    if (stat("/proc/self/exe", &st) == 0)
+        g.clock_reference = st.st_mtime;
+}
+
+/* Until NTP has set the clock, local time is meaningless and quiet hours
+ * could blank the screen at noon. Leave the slideshow running on the default
+ * hold duration until then. */
+static int clock_ready(void)
+{
+    if (g.clock_sane) return 1;
+    if (!clock_is_sane(time(NULL), g.clock_reference)) {
+        if (!g.clock_warned) {
+            fprintf(stderr, "Warning: system clock looks unset, "
+                    "ignoring quiet hours and hold schedule until it is\n");
+            g.clock_warned = 1;
+        }
+        return 0;
+    }
+    g.clock_sane = 1;
+    if (g.clock_warned)
+        printf("System clock set, quiet hours and hold schedule in effect\n");
+    return 1;
+}
+
+static int minute_of_day(void)
+{
+    time_t t = time(NULL);
+    struct tm tm;
+    localtime_r(&t, &tm);
+    return tm.tm_hour * 60 + tm.tm_min;
+}
+
+static int in_quiet_hours(void)
+{
+    if (!g.quiet_hours.enabled || !clock_ready()) return 0;
+    return time_in_range(&g.quiet_hours, minute_of_day());
+}
+
+static void start_hold(void)
+{
+    float hold = g.hold_duration;
+    if (g.hold_schedule.count > 0 && clock_ready())
+        hold = hold_duration_at(&g.hold_schedule, g.hold_duration, minute_of_day());
+
+    clock_gettime(CLOCK_MONOTONIC, &g.hold_deadline);
+    g.hold_deadline.tv_sec += (time_t)hold;
+    g.hold_deadline.tv_nsec += (long)((hold - (int)hold) * 1e9);
+    if (g.hold_deadline.tv_nsec >= 1000000000L) {
+        g.hold_deadline.tv_sec++;
+        g.hold_deadline.tv_nsec -= 1000000000L;
+    }
+    g.hold_complete = 0;
+}
+
+/* Draw the quiet-hours screen: black, or a dim red HH:MM clock. */
+static void render_quiet_frame(void)
+{
+    glClearColor(0.0f, 0.0f, 0.0f, 1.0f);
+    glClear(GL_COLOR_BUFFER_BIT);
+    if (!g.night_clock) return;
+
+    g.clock_minute = minute_of_day();
+    float rects[CLOCK_MAX_RECTS * 4];
+    int n = build_clock_rects(g.clock_minute / 60, g.clock_minute % 60,
+                              NIGHT_CLOCK_HEIGHT, g.screen_aspect, rects);
+
+    glBindTexture(GL_TEXTURE_2D, g.clock_tex);
+    glUniform1f(g.u_alpha_loc, g.night_clock_brightness);
+    set_color_uniforms(1);
+    for (int i = 0; i < n; ++i) {
+        const float *r = rects + 4 * i;
+        GLfloat verts[16] = {
+            r[0], r[1], 0.0f, 0.0f,
+            r[2], r[1], 0.0f, 0.0f,
+            r[0], r[3], 0.0f, 0.0f,
+            r[2], r[3], 0.0f, 0.0f,
+        };
+        glBufferSubData(GL_ARRAY_BUFFER, 0, sizeof(verts), verts);
+        glDrawArrays(GL_TRIANGLE_STRIP, 0, 4);
+    }
+    set_color_uniforms(0);
+}
+
 static void start_fade(int from_slot, int to_slot)
 {
     printf("Starting fade %d -> %d\n", from_slot, to_slot);
@@ -445,15 +667,7 @@ static void start_fade(int from_slot, int to_slot)
 static void advance_fade(void)
 {
     /* Promote pending framebuffer to scanout on every flip completion */
-    if (g.pending_fb.bo) {
-        if (g.scanout_fb.bo) {
-            drmModeRmFB(g.drm_fd, g.scanout_fb.fb_id);
-            gbm_surface_release_buffer(g.gbm_surf, g.scanout_fb.bo);
-        }
-        g.scanout_fb = g.pending_fb;
-        g.pending_fb.bo    = NULL;
-        g.pending_fb.fb_id = 0;
-    }
+    promote_pending_fb();
 
     struct timespec now;
     clock_gettime(CLOCK_MONOTONIC, &now);
@@ -495,14 +709,7 @@ static void advance_fade(void)
 
         send_ready();
 
-        clock_gettime(CLOCK_MONOTONIC, &g.hold_deadline);
-        g.hold_deadline.tv_sec += (time_t)HOLD_DURATION_SEC;
-        g.hold_deadline.tv_nsec += (long)((HOLD_DURATION_SEC - (int)HOLD_DURATION_SEC) * 1e9);
-        if (g.hold_deadline.tv_nsec >= 1000000000L) {
-            g.hold_deadline.tv_sec++;
-            g.hold_deadline.tv_nsec -= 1000000000L;
-        }
-        g.hold_complete = 0;
+        start_hold();
         return;
     }
 
@@ -532,7 +739,15 @@ int main(void)
     g.running = 1;
     struct display_config cfg = read_display_config();
     g.fade_duration = cfg.fade_duration;
+    g.hold_duration = cfg.hold_duration;
+    g.hold_schedule = cfg.hold_schedule;
     g.skip_frames = cfg.skip_frames;
+    g.quiet_hours = cfg.quiet_hours;
+    g.night_clock = cfg.night_clock;
+    g.night_clock_brightness = cfg.night_clock_brightness;
+    g.color = cfg.color;
+    g.watermark = cfg.watermark;
+    init_timezone();
 
     struct sigaction sa;
     memset(&sa, 0, sizeof(sa));
@@ -660,14 +875,43 @@ int main(void)
         "    gl_FragColor = texture2D(u_tex, v_tex) * u_alpha;\n"
         "}\n";
 
+    /* Only paid for when a correction is configured; pow() per fragment is
+     * not free on the Pi's GPU. */
+    const char *frag_color_src =
+        "precision mediump float;\n"
+        "varying vec2 v_tex;\n"
+        "uniform sampler2D u_tex;\n"
+        "uniform float u_alpha;\n"
+        "uniform mat3 u_color;\n"
+        "uniform float u_brightness;\n"
+        "uniform float u_contrast;\n"
+        "uniform float u_inv_gamma;\n"
+        "uniform float u_vignette;\n"
+        "void main() {\n"
+        "    vec4 c = texture2D(u_tex, v_tex);\n"
+        "    vec3 rgb = pow(c.rgb, vec3(u_inv_gamma));\n"
+        "    rgb = u_color * rgb;\n"
+        "    rgb = (rgb - 0.5) * u_contrast + 0.5 + u_brightness;\n"
+        "    float r = length(v_tex - 0.5) * 1.4142;\n"
+        "    rgb *= 1.0 - u_vignette * smoothstep(0.5, 1.0, r);\n"
+        "    gl_FragColor = vec4(clamp(rgb, 0.0, 1.0), c.a) * u_alpha;\n"
+        "}\n";
+
+    int adjust = !color_adjust_is_identity(&g.color);
     GLuint vs = compile_shader(GL_VERTEX_SHADER, vert_src);
-    GLuint fs = compile_shader(GL_FRAGMENT_SHADER, frag_src);
+    GLuint fs = compile_shader(GL_FRAGMENT_SHADER, adjust ? frag_color_src : frag_src);
     GLuint prog = link_program(vs, fs);
     glUseProgram(prog);
 
     GLint u_tex_loc   = glGetUniformLocation(prog, "u_tex");
     g.u_alpha_loc     = glGetUniformLocation(prog, "u_alpha");
+    g.u_color_loc     = adjust ? glGetUniformLocation(prog, "u_color") : -1;
+    g.u_brightness_loc = glGetUniformLocation(prog, "u_brightness");
+    g.u_contrast_loc  = glGetUniformLocation(prog, "u_contrast");
+    g.u_inv_gamma_loc = glGetUniformLocation(prog, "u_inv_gamma");
+    g.u_vignette_loc  = glGetUniformLocation(prog, "u_vignette");
     glUniform1i(u_tex_loc, 0);
+    set_color_uniforms(0);
 
     /* ---- Geometry buffer ----------------------------------------------- */
     GLuint buf;
@@ -695,6 +939,17 @@ int main(void)
         glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_WRAP_T, GL_CLAMP_TO_EDGE);
     }
 
+    /* Solid red texel for the night clock; u_alpha scales its brightness */
+    static const unsigned char clock_texel[4] = { 255, 0, 0, 255 };
+    glGenTextures(1, &g.clock_tex);
+    glBindTexture(GL_TEXTURE_2D, g.clock_tex);
+    glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_MIN_FILTER, GL_NEAREST);
+    glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_MAG_FILTER, GL_NEAREST);
+    glTexImage2D(GL_TEXTURE_2D, 0, GL_RGBA, 1, 1, 0,
+                 GL_RGBA, GL_UNSIGNED_BYTE, clock_texel);
+
+    load_watermark();
+
     /* ---- Socket setup -------------------------------------------------- */
     unlink(SOCKET_PATH);
     g.listen_fd = socket(AF_UNIX, SOCK_STREAM | SOCK_CLOEXEC, 0);
@@ -752,6 +1007,18 @@ int main(void)
                 timeout = (int)diff_ms;
             }
         }
+        /* Wake periodically so quiet hours start and end on time */
+        if (g.quiet_hours.enabled &&
+            (timeout < 0 || timeout > QUIET_CHECK_INTERVAL_MS)) {
+            timeout = QUIET_CHECK_INTERVAL_MS;
+        }
+        /* The night clock needs to wake on the minute boundary */
+        if (g.phase == PHASE_QUIET && g.night_clock) {
+            struct timespec wall;
+            clock_gettime(CLOCK_REALTIME, &wall);
+            int to_minute = (int)((60 - wall.tv_sec % 60) * 1000 - wall.tv_nsec / 1000000);
+            if (to_minute < timeout) timeout = to_minute;
+        }
 
         struct epoll_event events[4];
         int n = epoll_wait(g.epoll_fd, events, 4, timeout);
@@ -814,6 +1081,7 @@ int main(void)
                 glBindTexture(GL_TEXTURE_2D, g.slots[0].tex);
                 glUniform1f(g.u_alpha_loc, 1.0f);
                 glDrawArrays(GL_TRIANGLE_STRIP, 0, 4);
+                draw_watermark();
 
                 ok = eglSwapBuffers(g.egl_dpy, g.egl_surf);
                 EGL_CHECK(ok, ok, "eglSwapBuffers");
@@ -839,18 +1107,40 @@ int main(void)
 
                 g.phase = PHASE_HOLDING;
                 send_ready();
-                clock_gettime(CLOCK_MONOTONIC, &g.hold_deadline);
-                g.hold_deadline.tv_sec += (time_t)HOLD_DURATION_SEC;
-                g.hold_deadline.tv_nsec += (long)((HOLD_DURATION_SEC - (int)HOLD_DURATION_SEC) * 1e9);
-                if (g.hold_deadline.tv_nsec >= 1000000000L) {
-                    g.hold_deadline.tv_sec++;
-                    g.hold_deadline.tv_nsec -= 1000000000L;
-                }
-                g.hold_complete = 0;
+                start_hold();
             }
             continue;
         }
 
+        /* Quiet hours: blank the screen between slides until they end */
+        if (g.phase == PHASE_HOLDING && in_quiet_hours()) {
+            printf("Quiet hours started, blanking display\n");
+            render_quiet_frame();
+            request_page_flip();
+            g.phase = PHASE_QUIET;
+        }
+
+        if (g.phase == PHASE_QUIET) {
+            if (g.flip_done) {
+                g.flip_done = 0;
+                promote_pending_fb();
+                if (g.quiet_leaving) {
+                    g.quiet_leaving = 0;
+                    g.phase = PHASE_HOLDING;
+                    start_hold();
+                }
+            } else if (!g.pending_fb.bo && !in_quiet_hours()) {
+                printf("Quiet hours ended, resuming slideshow\n");
+                render_frame(0.0f, g.current_slot, g.current_slot);
+                request_page_flip();
+                g.quiet_leaving = 1;
+            } else if (!g.pending_fb.bo && g.night_clock &&
+                       minute_of_day() != g.clock_minute) {
+                render_quiet_frame();
+                request_page_flip();
+            }
+        }
+
         /* Holding -> start fade if we have a next image */
         if (g.phase == PHASE_HOLDING && g.hold_complete) {
             int next = 1 - g.current_slot;
@@ -61,12 +61,66 @@ static int test_read_display_config(void)
     // Save and clear env vars
     const char *old_fade = getenv("PHOTO_FRAME_FADE_DURATION");
     const char *old_skip = getenv("PHOTO_FRAME_SKIP_FRAMES");
+    const char *old_quiet = getenv("PHOTO_FRAME_QUIET_HOURS");
+    const char *old_clock = getenv("PHOTO_FRAME_NIGHT_CLOCK");
+    const char *old_bright = getenv("PHOTO_FRAME_NIGHT_CLOCK_BRIGHTNESS");
+    const char *old_gamma = getenv("PHOTO_FRAME_GAMMA");
     if (old_fade) unsetenv("PHOTO_FRAME_FADE_DURATION");
     if (old_skip) unsetenv("PHOTO_FRAME_SKIP_FRAMES");
+    if (old_quiet) unsetenv("PHOTO_FRAME_QUIET_HOURS");
+    if (old_clock) unsetenv("PHOTO_FRAME_NIGHT_CLOCK");
+    if (old_bright) unsetenv("PHOTO_FRAME_NIGHT_CLOCK_BRIGHTNESS");
+    if (old_gamma) unsetenv("PHOTO_FRAME_GAMMA");
 
     struct display_config cfg = read_display_config();
     TEST_ASSERT(cfg.fade_duration == 1.5f);
+    TEST_ASSERT(cfg.hold_duration == 5.0f);
+    TEST_ASSERT(cfg.hold_schedule.count == 0);
     TEST_ASSERT(cfg.skip_frames == 0);
+    TEST_ASSERT(!cfg.quiet_hours.enabled);
+    TEST_ASSERT(!cfg.night_clock);
+    TEST_ASSERT(cfg.night_clock_brightness == 0.25f);
+    TEST_ASSERT(color_adjust_is_identity(&cfg.color));
+
+    setenv("PHOTO_FRAME_GAMMA", "0", 1);
+    cfg = read_display_config();
+    TEST_ASSERT(cfg.color.gamma == 0.1f);
+    TEST_ASSERT(!color_adjust_is_identity(&cfg.color));
+    unsetenv("PHOTO_FRAME_GAMMA");
+
+    // Not a number: warn and keep the default rather than clamp 0
+    setenv("PHOTO_FRAME_CONTRAST", "abc", 1);
+    setenv("PHOTO_FRAME_HOLD_DURATION", "10s", 1);
+    setenv("PHOTO_FRAME_GAMMA", "nan", 1);
+    cfg = read_display_config();
+    TEST_ASSERT(cfg.color.contrast == DEFAULT_CONTRAST);
+    TEST_ASSERT(cfg.hold_duration == DEFAULT_HOLD_DURATION);
+    TEST_ASSERT(color_adjust_is_identity(&cfg.color));
+    setenv("PHOTO_FRAME_HOLD_DURATION", "10 ", 1);
+    cfg = read_display_config();
+    TEST_ASSERT(cfg.hold_duration == 10.0f);
+    unsetenv("PHOTO_FRAME_CONTRAST");
+    unsetenv("PHOTO_FRAME_HOLD_DURATION");
+    unsetenv("PHOTO_FRAME_GAMMA");
+
+    setenv("PHOTO_FRAME_QUIET_HOURS", "23:30-06:15", 1);
+    cfg = read_display_config();
+    TEST_ASSERT(cfg.quiet_hours.enabled);
+    TEST_ASSERT(cfg.quiet_hours.start_min == 23 * 60 + 30);
+    TEST_ASSERT(cfg.quiet_hours.end_min == 6 * 60 + 15);
+
+    setenv("PHOTO_FRAME_QUIET_HOURS", "bedtime", 1);
+    cfg = read_display_config();
+    TEST_ASSERT(!cfg.quiet_hours.enabled);
+    unsetenv("PHOTO_FRAME_QUIET_HOURS");
+
+    setenv("PHOTO_FRAME_NIGHT_CLOCK", "1", 1);
+    setenv("PHOTO_FRAME_NIGHT_CLOCK_BRIGHTNESS", "2.0", 1);
+    cfg = read_display_config();
+    TEST_ASSERT(cfg.night_clock);
+    TEST_ASSERT(cfg.night_clock_brightness == 1.0f);
+    unsetenv("PHOTO_FRAME_NIGHT_CLOCK");
+    unsetenv("PHOTO_FRAME_NIGHT_CLOCK_BRIGHTNESS");
 
     setenv("PHOTO_FRAME_FADE_DURATION", "3.0", 1);
     setenv("PHOTO_FRAME_SKIP_FRAMES", "2", 1);
@@ -85,11 +139,252 @@ static int test_read_display_config(void)
     else unsetenv("PHOTO_FRAME_FADE_DURATION");
     if (old_skip) setenv("PHOTO_FRAME_SKIP_FRAMES", old_skip, 1);
     else unsetenv("PHOTO_FRAME_SKIP_FRAMES");
+    if (old_quiet) setenv("PHOTO_FRAME_QUIET_HOURS", old_quiet, 1);
+    if (old_clock) setenv("PHOTO_FRAME_NIGHT_CLOCK", old_clock, 1);
+    if (old_bright) setenv("PHOTO_FRAME_NIGHT_CLOCK_BRIGHTNESS", old_bright, 1);
+    if (old_gamma) setenv("PHOTO_FRAME_GAMMA", old_gamma, 1);
 
     printf("PASS: read_display_config\n");
     return 0;
 }
 
+static int test_parse_time_range(void)
+{
+    struct time_range r;
+
+    TEST_ASSERT(parse_time_range("22:00-07:00", &r));
+    TEST_ASSERT(r.enabled && r.start_min == 22 * 60 && r.end_min == 7 * 60);
+
+    TEST_ASSERT(parse_time_range("00:00-24:00", &r));
+    TEST_ASSERT(r.start_min == 0 && r.end_min == 24 * 60);
+
+    TEST_ASSERT(!parse_time_range("22:00", &r));
+    TEST_ASSERT(!r.enabled);
+    TEST_ASSERT(!parse_time_range("25:00-07:00", &r));
+    TEST_ASSERT(!parse_time_range("22:0-07:00", &r));
+    TEST_ASSERT(!parse_time_range("22:00-07:00x", &r));
+    TEST_ASSERT(!parse_time_range("08:00-08:00", &r));
+    TEST_ASSERT(!parse_time_range("24:00-07:00", &r));
+
+    printf("PASS: parse_time_range\n");
+    return 0;
+}
+
+static int test_time_in_range(void)
+{
+    struct time_range day = { .enabled = 1, .start_min = 9 * 60, .end_min = 17 * 60 };
+    TEST_ASSERT(!time_in_range(&day, 8 * 60 + 59));
+    TEST_ASSERT(time_in_range(&day, 9 * 60));
+    TEST_ASSERT(time_in_range(&day, 16 * 60 + 59));
+    TEST_ASSERT(!time_in_range(&day, 17 * 60));
+
+    struct time_range night = { .enabled = 1, .start_min = 22 * 60, .end_min = 7 * 60 };
+    TEST_ASSERT(time_in_range(&night, 23 * 60));
+    TEST_ASSERT(time_in_range(&night, 0));
+    TEST_ASSERT(time_in_range(&night, 6 * 60 + 59));
+    TEST_ASSERT(!time_in_range(&night, 7 * 60));
+    TEST_ASSERT(!time_in_range(&night, 12 * 60));
+
+    struct time_range all_day = { .enabled = 1, .start_min = 0, .end_min = 24 * 60 };
+    TEST_ASSERT(time_in_range(&all_day, 0));
+    TEST_ASSERT(time_in_range(&all_day, 23 * 60 + 59));
+
+    struct time_range disabled = { .enabled = 0, .start_min = 0, .end_min = 24 * 60 };
+    TEST_ASSERT(!time_in_range(&disabled, 12 * 60));
+
+    printf("PASS: time_in_range\n");
+    return 0;
+}
+
+static int test_parse_hold_schedule(void)
+{
+    struct hold_schedule s;
+    TEST_ASSERT(parse_hold_schedule("18:00-23:00=60,06:00-08:30=20.5", &s));
+    TEST_ASSERT(s.count == 2);
+    TEST_ASSERT(s.rules[0].range.start_min == 18 * 60 && s.rules[0].seconds == 60.0f);
+    TEST_ASSERT(s.rules[1].range.end_min == 8 * 60 + 30 && s.rules[1].seconds == 20.5f);
+
+    TEST_ASSERT(hold_duration_at(&s, 10.0f, 12 * 60) == 10.0f);
+    TEST_ASSERT(hold_duration_at(&s, 10.0f, 19 * 60) == 60.0f);
+    TEST_ASSERT(hold_duration_at(&s, 10.0f, 7 * 60) == 20.5f);
+    TEST_ASSERT(hold_duration_at(&s, 10.0f, 23 * 60) == 10.0f);
+
+    /* Overlaps: the first rule wins */
+    TEST_ASSERT(parse_hold_schedule("20:00-06:00=120,18:00-24:00=60", &s));
+    TEST_ASSERT(hold_duration_at(&s, 10.0f, 21 * 60) == 120.0f);
+    TEST_ASSERT(hold_duration_at(&s, 10.0f, 19 * 60) == 60.0f);
+
+    TEST_ASSERT(!parse_hold_schedule("18:00-23:00", &s));
+    TEST_ASSERT(s.count == 0);
+    TEST_ASSERT(!parse_hold_schedule("18:00-23:00=0", &s));
+    TEST_ASSERT(!parse_hold_schedule("18:00-23:00=60s", &s));
+    TEST_ASSERT(!parse_hold_schedule("18:00-23:00=60,evening=30", &s));
+    TEST_ASSERT(!parse_hold_schedule("", &s));
+    TEST_ASSERT(hold_duration_at(&s, 10.0f, 19 * 60) == 10.0f);
+
+    printf("PASS: parse_hold_schedule\n");
+    return 0;
+}
+
+static int test_clock_is_sane(void)
+{
+    TEST_ASSERT(!clock_is_sane(0, 0));
+    TEST_ASSERT(!clock_is_sane(MIN_SANE_TIME - 1, 0));
+    TEST_ASSERT(clock_is_sane(MIN_SANE_TIME, 0));
+    TEST_ASSERT(clock_is_sane(MIN_SANE_TIME + 86400, MIN_SANE_TIME));
+    /* Behind a file written on an earlier boot: fake-hwclock, not NTP */
+    TEST_ASSERT(!clock_is_sane(MIN_SANE_TIME + 60, MIN_SANE_TIME + 86400));
+
+    printf("PASS: clock_is_sane\n");
+    return 0;
+}
+
+static int test_seven_segment_mask(void)
+{
+    TEST_ASSERT(seven_segment_mask(8) == 0x7F);
+    TEST_ASSERT(seven_segment_mask(1) == 0x06);
+    TEST_ASSERT(seven_segment_mask(0) == 0x3F);
+    TEST_ASSERT(seven_segment_mask(-1) == 0);
+    TEST_ASSERT(seven_segment_mask(10) == 0);
+
+    printf("PASS: seven_segment_mask\n");
+    return 0;
+}
+
+static int test_build_clock_rects(void)
+{
+    float rects[CLOCK_MAX_RECTS * 4];
+
+    /* 1 + 8 + 0 + 8 segments, plus two colon dots */
+    int n = build_clock_rects(18, 8, 0.5f, 16.0f / 9.0f, rects);
+    TEST_ASSERT(n == 2 + 7 + 6 + 7 + 2);
+
+    /* 2 + 0 + 0 + 8: both outer digits span their full cell width */
+    n = build_clock_rects(20, 8, 0.5f, 16.0f / 9.0f, rects);
+    TEST_ASSERT(n == 5 + 6 + 6 + 7 + 2);
+
+    float min_x = 1.0f, max_x = -1.0f;
+    for (int i = 0; i < n; ++i) {
+        float *r = rects + 4 * i;
+        TEST_ASSERT(r[0] < r[2] && r[1] < r[3]);
+        TEST_ASSERT(r[1] >= -0.25f && r[3] <= 0.25f);
+        if (r[0] < min_x) min_x = r[0];
+        if (r[2] > max_x) max_x = r[2];
+    }
+    /* Centered horizontally and on screen */
+    TEST_ASSERT(min_x > -1.0f && max_x < 1.0f);
+    TEST_ASSERT(min_x + max_x < 0.001f && min_x + max_x > -0.001f);
+
+    TEST_ASSERT(build_clock_rects(23, 59, 0.5f, 1.0f, rects) <= CLOCK_MAX_RECTS);
+
+    printf("PASS: build_clock_rects\n");
+    return 0;
+}
+
+static int near(float a, float b)
+{
+    return a - b < 1e-5f && b - a < 1e-5f;
+}
+
+static int test_build_color_matrix(void)
+{
+    float m[9];
+    struct color_adjust c = {
+        DEFAULT_BRIGHTNESS, DEFAULT_CONTRAST, DEFAULT_SATURATION, DEFAULT_GAMMA,
+        FILTER_NONE, 0.0f
+    };
+
+    // Saturation 1 is the identity
+    build_color_matrix(&c, m);
+    for (int i = 0; i < 9; ++i) {
+        TEST_ASSERT(near(m[i], (i % 4 == 0) ? 1.0f : 0.0f));
+    }
+
+    // Saturation 0 maps every channel to luma: each row sums to 1 and
+    // all rows are equal
+    c.saturation = 0.0f;
+    build_color_matrix(&c, m);
+    for (int row = 0; row < 3; ++row) {
+        TEST_ASSERT(near(m[0 + row] + m[3 + row] + m[6 + row], 1.0f));
+        TEST_ASSERT(near(m[3 + row], 0.587f));
+    }
+
+    // Boosted saturation keeps gray gray
+    c.saturation = 2.0f;
+    build_color_matrix(&c, m);
+    TEST_ASSERT(near(m[0] + m[3] + m[6], 1.0f));
+
+    // The grayscale filter wins over any saturation
+    c.filter = FILTER_GRAYSCALE;
+    build_color_matrix(&c, m);
+    TEST_ASSERT(near(m[0], 0.299f) && near(m[1], 0.299f) && near(m[2], 0.299f));
+
+    // Sepia warms gray: red out > green out > blue out
+    c.saturation = 1.0f;
+    c.filter = FILTER_SEPIA;
+    build_color_matrix(&c, m);
+    float r = m[0] + m[3] + m[6], gr = m[1] + m[4] + m[7], b = m[2] + m[5] + m[8];
+    TEST_ASSERT(r > gr && gr > b);
+
+    printf("PASS: build_color_matrix\n");
+    return 0;
+}
+
+static int test_parse_photo_filter(void)
+{
+    enum photo_filter f = FILTER_NONE;
+
+    TEST_ASSERT(parse_photo_filter("sepia", &f) && f == FILTER_SEPIA);
+    TEST_ASSERT(parse_photo_filter("greyscale", &f) && f == FILTER_GRAYSCALE);
+    TEST_ASSERT(parse_photo_filter("none", &f) && f == FILTER_NONE);
+    TEST_ASSERT(!parse_photo_filter("vintage", &f));
+    TEST_ASSERT(f == FILTER_NONE);
+
+    printf("PASS: parse_photo_filter\n");
+    return 0;
+}
+
+static int test_build_watermark_quad(void)
+{
+    float v[16];
+    enum watermark_position pos = WATERMARK_BOTTOM_RIGHT;
+
+    TEST_ASSERT(parse_watermark_position("top-left", &pos) && pos == WATERMARK_TOP_LEFT);
+    TEST_ASSERT(!parse_watermark_position("center", &pos));
+    TEST_ASSERT(pos == WATERMARK_TOP_LEFT);
+
+    // Square logo, 10% of the screen height, bottom right of a 16:9 screen
+    build_watermark_quad(1.0f, 16.0f / 9.0f, 0.1f, WATERMARK_BOTTOM_RIGHT, v);
+    TEST_ASSERT(near(v[13] - v[1], 0.2f));                  // 10% of 2 NDC units
+    TEST_ASSERT(near((v[4] - v[0]) * 16.0f / 9.0f, 0.2f));  // square on screen
+    TEST_ASSERT(v[4] < 1.0f && v[4] > 0.9f);
+    TEST_ASSERT(v[1] > -1.0f && v[1] < -0.9f);
+    TEST_ASSERT(v[3] == 1.0f && v[11] == 0.0f);             // not flipped
+
+    // Top left mirrors it
+    build_watermark_quad(1.0f, 16.0f / 9.0f, 0.1f, WATERMARK_TOP_LEFT, v);
+    TEST_ASSERT(v[0] > -1.0f && v[0] < -0.9f);
+    TEST_ASSERT(v[13] < 1.0f && v[13] > 0.9f);
+
+    printf("PASS: build_watermark_quad\n");
+    return 0;
+}
+
+static int test_premultiply_alpha(void)
+{
+    unsigned char px[8] = { 200, 100, 50, 255,   200, 100, 50, 0 };
+    premultiply_alpha(px, 2);
+    TEST_ASSERT(px[0] == 200 && px[1] == 100 && px[2] == 50 && px[3] == 255);
+    TEST_ASSERT(px[4] == 0 && px[5] == 0 && px[6] == 0 && px[7] == 0);
+
+    unsigned char half[4] = { 255, 255, 255, 128 };
+    premultiply_alpha(half, 1);
+    TEST_ASSERT(half[0] == 128 && half[3] == 128);
+
+    printf("PASS: premultiply_alpha\n");
+    return 0;
+}
+
 static int test_select_image_destination(void)
 {
     TEST_ASSERT(select_image_destination(0, 0, 0) == 0);
@@ -179,6 +474,16 @@ int main(void)
     int failures = 0;
     failures += test_build_quad();
     failures += test_read_display_config();
+    failures += test_parse_time_range();
+    failures += test_time_in_range();
+    failures += test_parse_hold_schedule();
+    failures += test_clock_is_sane();
+    failures += test_seven_segment_mask();
+    failures += test_build_clock_rects();
+    failures += test_build_color_matrix();
+    failures += test_parse_photo_filter();
+    failures += test_build_watermark_quad();
+    failures += test_premultiply_alpha();
     failures += test_select_image_destination();
     failures += test_parse_protocol_buffer();
     if (failures == 0) {
@@ -14,8 +14,9 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
+use crate::crash;
 use crate::display::DisplayClient;
-use crate::index::{self, IndexReader};
+use crate::index::{self, IndexMetadata, IndexReader};
 use notify::{Config as NotifyConfig, Event, RecommendedWatcher, RecursiveMode, Watcher};
 use std::io;
 use std::path::Path;
@@ -23,10 +24,68 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
+/// Line to start the slideshow from. The same seed and index always give the
+/// same line, so a bug report with `--seed` reproduces the photo order.
+pub fn pick_start_line(metadata: &IndexMetadata, seed: u64) -> usize {
+    if metadata.valid_count == 0 {
+        return metadata.start_line;
+    }
+    metadata.start_line + (seed % metadata.valid_count as u64) as usize
+}
+
+/// A seed for `pick_start_line` when none was given on the command line.
+fn random_seed() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_nanos() as u64
+}
+
+/// First line added when the index grows from `old` to `new`, or `None` if
+/// nothing was appended. Lines rotated out in the meantime are skipped.
+fn first_new_line(old: &IndexMetadata, new: &IndexMetadata) -> Option<usize> {
+    if new.total_lines() > old.total_lines() {
+        Some(old.total_lines().max(new.start_line))
+    } else {
+        None
+    }
+}
+
+/// What the display loop does with the next record from the index.
+#[derive(Debug, PartialEq)]
+enum RecordAction {
+    Send,
+    /// The file is gone (rotated out or deleted by hand), try the next one.
+    Skip,
+    /// A whole index worth of records in a row was missing, back off.
+    Wait,
+}
+
+/// Decide what to do with the record for `path`. `missing_run` counts
+/// consecutive missing files, to tell a few stale entries from an index with
+/// nothing left to show.
+fn record_action(path: &Path, missing_run: &mut usize, valid_count: usize) -> RecordAction {
+    if path.exists() {
+        *missing_run = 0;
+        return RecordAction::Send;
+    }
+    *missing_run += 1;
+    if *missing_run >= valid_count.max(1) {
+        *missing_run = 0;
+        RecordAction::Wait
+    } else {
+        RecordAction::Skip
+    }
+}
+
 /// Run the display loop: stream photos from the index and send them to the display app.
+/// With `show_new_first`, photos appended while it runs are shown next, then
+/// the slideshow resumes where it left off.
 pub fn run_display_loop(
     index_dir: &Path,
     socket_path: &Path,
+    seed: Option<u64>,
+    show_new_first: bool,
     shutdown: Arc<AtomicBool>,
 ) -> io::Result<()> {
     let (index_path, mut metadata) = index::init_index(index_dir)?;
@@ -44,21 +103,12 @@ pub fn run_display_loop(
     let mut reader = IndexReader::open(&index_path, metadata)?;
 
     // Pick a random starting line within the valid range
-    let valid_count = metadata.valid_count;
-    let start_line = if valid_count > 0 {
-        let random_offset = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_nanos() as usize
-            % valid_count;
-        metadata.start_line + random_offset
-    } else {
-        metadata.start_line
-    };
+    let seed = seed.unwrap_or_else(random_seed);
+    let start_line = pick_start_line(&metadata, seed);
 
-    if valid_count > 0 {
+    if metadata.valid_count > 0 {
         reader.seek_to(start_line)?;
-        log::info!("Starting display from line {}", start_line);
+        log::info!("Starting display from line {} (seed {})", start_line, seed);
     }
 
     let mut display = DisplayClient::new(socket_path);
@@ -80,6 +130,9 @@ pub fn run_display_loop(
         .map_err(|e| io::Error::other(e.to_string()))?;
 
     let mut current_line = reader.current_line();
+    let mut missing_run = 0;
+    // Where to go back to once newly imported photos have been shown
+    let mut resume_line: Option<usize> = None;
 
     loop {
         if shutdown.load(Ordering::Relaxed) {
@@ -95,8 +148,17 @@ pub fn run_display_loop(
                     log::info!("Index file changed, reopening");
                     // Re-init index and seek to previous position
                     let (new_path, new_meta) = index::init_index(index_dir)?;
+                    let new_line = first_new_line(&metadata, &new_meta).filter(|_| show_new_first);
                     metadata = new_meta;
                     reader = IndexReader::open(&new_path, metadata)?;
+                    if let Some(line) = new_line {
+                        // Keep the first resume point if photos arrive mid catch-up
+                        if resume_line.is_none() {
+                            resume_line = Some(current_line);
+                            current_line = line;
+                            log::info!("Showing new photos from line {}", line);
+                        }
+                    }
                     if let Err(e) = reader.seek_to(current_line) {
                         log::warn!("Failed to seek to previous position: {}", e);
                         // If seek fails, just start from the beginning of valid lines
@@ -110,6 +172,23 @@ pub fn run_display_loop(
         match reader.next_record() {
             Ok(Some(record)) => {
                 current_line = record.line_number + 1;
+                match record_action(
+                    Path::new(&record.path),
+                    &mut missing_run,
+                    metadata.valid_count,
+                ) {
+                    RecordAction::Send => {}
+                    RecordAction::Skip => {
+                        log::debug!("Skipping missing photo {}", record.path);
+                        continue;
+                    }
+                    RecordAction::Wait => {
+                        log::warn!("No photo in the index exists on disk, waiting");
+                        std::thread::sleep(Duration::from_secs(5));
+                        continue;
+                    }
+                }
+                crash::set_last_photo(&record.path);
                 if let Err(e) = display.send_img(&record.path) {
                     log::warn!("Failed to send image to display: {}", e);
                     // Wait a bit before retrying
@@ -118,7 +197,15 @@ pub fn run_display_loop(
             }
             Ok(None) => {
                 // EOF reached, wrap to start_line
-                if metadata.valid_count > 0 {
+                if let Some(line) = resume_line.take() {
+                    log::info!("New photos shown, resuming at line {}", line);
+                    let line = line.max(metadata.start_line);
+                    if let Err(e) = reader.seek_to(line) {
+                        log::warn!("Failed to resume at line {}: {}", line, e);
+                        let _ = reader.seek_to(metadata.start_line);
+                    }
+                    current_line = line;
+                } else if metadata.valid_count > 0 {
                     log::debug!("Reached end of index, wrapping to start");
                     if let Err(e) = reader.seek_to(metadata.start_line) {
                         log::warn!("Failed to wrap to start: {}", e);
@@ -139,3 +226,83 @@ pub fn run_display_loop(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pick_start_line() {
+        let metadata = IndexMetadata {
+            start_line: 40,
+            valid_count: 10,
+        };
+        assert_eq!(pick_start_line(&metadata, 0), 40);
+        assert_eq!(pick_start_line(&metadata, 7), 47);
+        assert_eq!(
+            pick_start_line(&metadata, 7),
+            pick_start_line(&metadata, 17)
+        );
+        for seed in [1, 9, 12345, u64::MAX] {
+            let line = pick_start_line(&metadata, seed);
+            assert!((40..50).contains(&line));
+        }
+
+        let empty = IndexMetadata {
+            start_line: 5,
+            valid_count: 0,
+        };
+        assert_eq!(pick_start_line(&empty, 3), 5);
+    }
+
+    #[test]
+    fn test_record_action_skips_missing_photo() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let present = tmpdir.path().join("present.jpg");
+        std::fs::write(&present, b"jpeg").unwrap();
+        let gone = tmpdir.path().join("gone.jpg");
+
+        // A vanished file is skipped and the next record is sent
+        let mut missing_run = 0;
+        assert_eq!(
+            record_action(&gone, &mut missing_run, 3),
+            RecordAction::Skip
+        );
+        assert_eq!(
+            record_action(&present, &mut missing_run, 3),
+            RecordAction::Send
+        );
+        assert_eq!(missing_run, 0);
+    }
+
+    #[test]
+    fn test_record_action_waits_when_all_missing() {
+        let gone = Path::new("/nonexistent/gone.jpg");
+
+        // Every record missing: wait once per pass over the index, not spin
+        let mut missing_run = 0;
+        for _ in 0..2 {
+            assert_eq!(record_action(gone, &mut missing_run, 3), RecordAction::Skip);
+            assert_eq!(record_action(gone, &mut missing_run, 3), RecordAction::Skip);
+            assert_eq!(record_action(gone, &mut missing_run, 3), RecordAction::Wait);
+        }
+
+        // A single-record index waits right away
+        assert_eq!(record_action(gone, &mut missing_run, 1), RecordAction::Wait);
+        assert_eq!(record_action(gone, &mut missing_run, 0), RecordAction::Wait);
+    }
+
+    #[test]
+    fn test_first_new_line() {
+        let meta = |start_line, valid_count| IndexMetadata {
+            start_line,
+            valid_count,
+        };
+        assert_eq!(first_new_line(&meta(0, 10), &meta(0, 12)), Some(10));
+        // Rotation and import in one go
+        assert_eq!(first_new_line(&meta(0, 10), &meta(20, 5)), Some(20));
+        assert_eq!(first_new_line(&meta(20, 5), &meta(0, 0)), None);
+        assert_eq!(first_new_line(&meta(0, 10), &meta(0, 10)), None);
+        assert_eq!(first_new_line(&meta(0, 10), &meta(5, 5)), None);
+    }
+}
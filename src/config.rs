@@ -16,7 +16,8 @@
 
 use serde::{Deserialize, Serialize};
 use std::fmt;
-use std::path::PathBuf;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
 pub enum AspectRatioMode {
@@ -29,6 +30,8 @@ pub enum AspectRatioMode {
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct Config {
+    #[serde(default)]
+    pub frame_name: Option<String>,
     pub photos_dir: PathBuf,
     pub socket_path: PathBuf,
     pub native_resolution: String,
@@ -36,16 +39,46 @@ pub struct Config {
     pub aspect_ratio_mode: AspectRatioMode,
     #[serde(default = "default_batch_delete_size")]
     pub batch_delete_size: usize,
+    #[serde(default = "default_min_photos_kept")]
+    pub min_photos_kept: usize,
     #[serde(default = "default_log_max_size")]
     pub log_max_size: usize,
     #[serde(default = "default_log_max_files")]
     pub log_max_files: usize,
+    #[serde(default = "default_log_dir")]
+    pub log_dir: PathBuf,
+    #[serde(default)]
+    pub hook_script: Option<PathBuf>,
+    #[serde(default)]
+    pub inbox_dir: Option<PathBuf>,
+    #[serde(default)]
+    pub show_new_photos_immediately: bool,
+    #[serde(default)]
+    pub max_import_size: u64,
+    #[serde(default)]
+    pub strip_metadata: bool,
+    #[serde(default)]
+    pub skip_bad_exposure: bool,
+    #[serde(default)]
+    pub min_free_space: u64,
+    #[serde(default = "default_monitor_interval_secs")]
+    pub monitor_interval_secs: u64,
+    #[serde(default = "default_cpu_temp_warning")]
+    pub cpu_temp_warning: f32,
+    #[serde(default = "default_memory_growth_warning")]
+    pub memory_growth_warning: f32,
+    #[serde(default = "default_memory_log_interval_secs")]
+    pub memory_log_interval_secs: u64,
 }
 
 fn default_batch_delete_size() -> usize {
     20
 }
 
+fn default_min_photos_kept() -> usize {
+    100
+}
+
 fn default_log_max_size() -> usize {
     262_144 // 256 KiB
 }
@@ -54,12 +87,32 @@ fn default_log_max_files() -> usize {
     2
 }
 
+fn default_log_dir() -> PathBuf {
+    PathBuf::from("/tmp")
+}
+
+fn default_monitor_interval_secs() -> u64 {
+    60
+}
+
+fn default_cpu_temp_warning() -> f32 {
+    75.0
+}
+
+fn default_memory_growth_warning() -> f32 {
+    4.0 // MiB per hour
+}
+
+fn default_memory_log_interval_secs() -> u64 {
+    3600
+}
+
 impl Config {
-    pub fn from_file(path: &std::path::Path) -> Result<Self, String> {
-        let contents = std::fs::read_to_string(path)
-            .map_err(|e| format!("Failed to read config file: {}", e))?;
-        let mut config: Config =
-            toml::from_str(&contents).map_err(|e| format!("Failed to parse config file: {}", e))?;
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let table = load_with_includes(path, &mut Vec::new())?;
+        let mut config: Config = toml::Value::Table(table)
+            .try_into()
+            .map_err(|e| format!("Failed to parse config file: {}", e))?;
         config.validate()?;
         config.photos_dir = config
             .photos_dir
@@ -100,10 +153,61 @@ impl Config {
             return Err("native_resolution width and height must be greater than 0".to_string());
         }
 
+        if let Some(name) = &self.frame_name {
+            if name.trim().is_empty() || name.chars().any(char::is_control) {
+                return Err(format!(
+                    "frame_name must be non-empty printable text, got: {:?}",
+                    name
+                ));
+            }
+        }
+
         if self.batch_delete_size == 0 {
             return Err("batch_delete_size must be greater than 0".to_string());
         }
 
+        if self.cpu_temp_warning.is_nan() || self.cpu_temp_warning <= 0.0 {
+            return Err("cpu_temp_warning must be greater than 0".to_string());
+        }
+
+        if self.memory_growth_warning.is_nan() || self.memory_growth_warning < 0.0 {
+            return Err("memory_growth_warning must not be negative".to_string());
+        }
+
+        if let Some(inbox) = &self.inbox_dir {
+            if !inbox.is_dir() {
+                return Err(format!("inbox_dir is not a directory: {}", inbox.display()));
+            }
+            // Imported files are deleted from the inbox, so it must not
+            // overlap the library.
+            let inbox = inbox
+                .canonicalize()
+                .map_err(|e| format!("Failed to resolve inbox_dir: {}", e))?;
+            let photos = self
+                .photos_dir
+                .canonicalize()
+                .map_err(|e| format!("Failed to resolve photos_dir: {}", e))?;
+            if inbox.starts_with(&photos) || photos.starts_with(&inbox) {
+                return Err(format!(
+                    "inbox_dir must be outside photos_dir: {}",
+                    inbox.display()
+                ));
+            }
+        }
+
+        if let Some(script) = &self.hook_script {
+            let mode = std::fs::metadata(script)
+                .map_err(|e| format!("hook_script {}: {}", script.display(), e))?
+                .permissions()
+                .mode();
+            if !script.is_file() || mode & 0o111 == 0 {
+                return Err(format!(
+                    "hook_script is not an executable file: {}",
+                    script.display()
+                ));
+            }
+        }
+
         Ok(())
     }
 
@@ -116,20 +220,108 @@ impl Config {
     }
 }
 
+/// Check that `dir` accepts new files by creating and removing a probe file.
+pub fn check_writable(dir: &Path) -> std::io::Result<()> {
+    let probe = dir.join(format!(".photo-frame-write-test-{}", std::process::id()));
+    std::fs::File::create(&probe)?;
+    std::fs::remove_file(&probe)
+}
+
+/// Read a config file and the files named in its `include` key (a path or
+/// a list of paths, relative to the including file). Included files are
+/// merged in order, each overriding the ones before it, and the including
+/// file's own keys override them all. `stack` holds the files being loaded,
+/// to detect cycles.
+fn load_with_includes(path: &Path, stack: &mut Vec<PathBuf>) -> Result<toml::Table, String> {
+    let canonical = path
+        .canonicalize()
+        .map_err(|e| format!("Failed to read config file {}: {}", path.display(), e))?;
+    if stack.contains(&canonical) {
+        return Err(format!(
+            "Config include cycle: {} includes itself",
+            canonical.display()
+        ));
+    }
+
+    let contents = std::fs::read_to_string(&canonical)
+        .map_err(|e| format!("Failed to read config file {}: {}", path.display(), e))?;
+    let mut own: toml::Table = toml::from_str(&contents)
+        .map_err(|e| format!("Failed to parse config file {}: {}", path.display(), e))?;
+
+    let includes = match own.remove("include") {
+        None => Vec::new(),
+        Some(toml::Value::String(s)) => vec![s],
+        Some(toml::Value::Array(items)) => items
+            .into_iter()
+            .map(|v| match v {
+                toml::Value::String(s) => Ok(s),
+                other => Err(format!("include entries must be paths, got: {}", other)),
+            })
+            .collect::<Result<_, _>>()?,
+        Some(other) => {
+            return Err(format!(
+                "include must be a path or a list of paths, got: {}",
+                other
+            ))
+        }
+    };
+
+    stack.push(canonical.clone());
+    let base_dir = canonical.parent().unwrap_or(Path::new("/"));
+    let mut merged = toml::Table::new();
+    for include in includes {
+        let fragment = load_with_includes(&base_dir.join(include), stack)?;
+        merge_tables(&mut merged, fragment);
+    }
+    stack.pop();
+
+    merge_tables(&mut merged, own);
+    Ok(merged)
+}
+
+/// Merge `overlay` into `base`. Nested tables are merged key by key; any
+/// other value in `overlay` replaces the one in `base`.
+fn merge_tables(base: &mut toml::Table, overlay: toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(existing)), toml::Value::Table(table)) => {
+                merge_tables(existing, table);
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
 impl fmt::Display for Config {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let (w, h) = self.resolution();
         write!(
             f,
-            "Config {{ photos_dir: {}, socket_path: {}, resolution: {}x{}, aspect_ratio_mode: {:?}, batch_delete_size: {}, log_max_size: {}, log_max_files: {} }}",
+            "Config {{ frame_name: {:?}, photos_dir: {}, socket_path: {}, resolution: {}x{}, aspect_ratio_mode: {:?}, batch_delete_size: {}, min_photos_kept: {}, log_max_size: {}, log_max_files: {}, log_dir: {}, hook_script: {:?}, inbox_dir: {:?}, show_new_photos_immediately: {}, max_import_size: {}, strip_metadata: {}, skip_bad_exposure: {}, min_free_space: {}, monitor_interval_secs: {}, cpu_temp_warning: {}, memory_growth_warning: {}, memory_log_interval_secs: {} }}",
+            self.frame_name,
             self.photos_dir.display(),
             self.socket_path.display(),
             w,
             h,
             self.aspect_ratio_mode,
             self.batch_delete_size,
+            self.min_photos_kept,
             self.log_max_size,
-            self.log_max_files
+            self.log_max_files,
+            self.log_dir.display(),
+            self.hook_script,
+            self.inbox_dir,
+            self.show_new_photos_immediately,
+            self.max_import_size,
+            self.strip_metadata,
+            self.skip_bad_exposure,
+            self.min_free_space,
+            self.monitor_interval_secs,
+            self.cpu_temp_warning,
+            self.memory_growth_warning,
+            self.memory_log_interval_secs
         )
     }
 }
@@ -142,15 +334,29 @@ mod tests {
     #[test]
     fn test_parse_valid_config() {
         let toml_str = r#"
+frame_name = "Living room"
 photos_dir = "/tmp/photos"
 socket_path = "/run/photo-frame/photo-frame.sock"
 native_resolution = "1920x1080"
 aspect_ratio_mode = "fit"
 batch_delete_size = 10
+min_photos_kept = 50
 log_max_size = 131072
 log_max_files = 3
+log_dir = "/run/photo-frame"
+inbox_dir = "/srv/inbox"
+show_new_photos_immediately = true
+max_import_size = 52428800
+strip_metadata = true
+skip_bad_exposure = true
+min_free_space = 104857600
+monitor_interval_secs = 30
+cpu_temp_warning = 70.5
+memory_growth_warning = 2.5
+memory_log_interval_secs = 600
 "#;
         let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.frame_name.as_deref(), Some("Living room"));
         assert_eq!(config.photos_dir, PathBuf::from("/tmp/photos"));
         assert_eq!(
             config.socket_path,
@@ -159,8 +365,20 @@ log_max_files = 3
         assert_eq!(config.native_resolution, "1920x1080");
         assert_eq!(config.aspect_ratio_mode, AspectRatioMode::Fit);
         assert_eq!(config.batch_delete_size, 10);
+        assert_eq!(config.min_photos_kept, 50);
         assert_eq!(config.log_max_size, 131_072);
         assert_eq!(config.log_max_files, 3);
+        assert_eq!(config.log_dir, PathBuf::from("/run/photo-frame"));
+        assert_eq!(config.inbox_dir, Some(PathBuf::from("/srv/inbox")));
+        assert!(config.show_new_photos_immediately);
+        assert_eq!(config.max_import_size, 52_428_800);
+        assert!(config.strip_metadata);
+        assert!(config.skip_bad_exposure);
+        assert_eq!(config.min_free_space, 104_857_600);
+        assert_eq!(config.monitor_interval_secs, 30);
+        assert_eq!(config.cpu_temp_warning, 70.5);
+        assert_eq!(config.memory_growth_warning, 2.5);
+        assert_eq!(config.memory_log_interval_secs, 600);
     }
 
     #[test]
@@ -171,10 +389,77 @@ socket_path = "/run/photo-frame/photo-frame.sock"
 native_resolution = "800x600"
 "#;
         let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.frame_name, None);
         assert_eq!(config.aspect_ratio_mode, AspectRatioMode::Fit);
         assert_eq!(config.batch_delete_size, 20);
+        assert_eq!(config.min_photos_kept, 100);
         assert_eq!(config.log_max_size, 262_144);
         assert_eq!(config.log_max_files, 2);
+        assert_eq!(config.log_dir, PathBuf::from("/tmp"));
+        assert_eq!(config.hook_script, None);
+        assert_eq!(config.inbox_dir, None);
+        assert!(!config.show_new_photos_immediately);
+        assert_eq!(config.max_import_size, 0);
+        assert!(!config.strip_metadata);
+        assert!(!config.skip_bad_exposure);
+        assert_eq!(config.min_free_space, 0);
+        assert_eq!(config.monitor_interval_secs, 60);
+        assert_eq!(config.cpu_temp_warning, 75.0);
+        assert_eq!(config.memory_growth_warning, 4.0);
+        assert_eq!(config.memory_log_interval_secs, 3600);
+    }
+
+    #[test]
+    fn test_validate_hook_script() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let script = tmpdir.path().join("hook.sh");
+        std::fs::write(&script, "#!/bin/sh\n").unwrap();
+
+        let toml_str = format!(
+            r#"
+photos_dir = "/tmp"
+socket_path = "/tmp/sock"
+native_resolution = "1920x1080"
+hook_script = "{}"
+"#,
+            script.display()
+        );
+        let config: Config = toml::from_str(&toml_str).unwrap();
+        // Not executable yet
+        assert!(config.validate().is_err());
+
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_inbox_dir() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let photos = tmpdir.path().join("photos");
+        std::fs::create_dir_all(photos.join("inbox")).unwrap();
+        std::fs::create_dir(tmpdir.path().join("inbox")).unwrap();
+
+        let config_with_inbox = |inbox: &Path| {
+            let toml_str = format!(
+                r#"
+photos_dir = "{}"
+socket_path = "/tmp/sock"
+native_resolution = "1920x1080"
+inbox_dir = "{}"
+"#,
+                photos.display(),
+                inbox.display()
+            );
+            toml::from_str::<Config>(&toml_str).unwrap()
+        };
+        assert!(config_with_inbox(&tmpdir.path().join("inbox"))
+            .validate()
+            .is_ok());
+        assert!(config_with_inbox(&photos.join("inbox")).validate().is_err());
+        assert!(config_with_inbox(tmpdir.path()).validate().is_err());
+        assert!(config_with_inbox(&tmpdir.path().join("missing"))
+            .validate()
+            .is_err());
     }
 
     #[test]
@@ -188,6 +473,71 @@ native_resolution = "abcxdef"
         assert!(config.validate().is_err());
     }
 
+    #[test]
+    fn test_validate_cpu_temp_warning() {
+        let toml_str = r#"
+photos_dir = "/tmp"
+socket_path = "/tmp/sock"
+native_resolution = "1920x1080"
+cpu_temp_warning = 0
+"#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_from_file_with_includes() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(tmpdir.path().join("shared")).unwrap();
+        std::fs::write(
+            tmpdir.path().join("shared/base.toml"),
+            r#"
+photos_dir = "/tmp"
+socket_path = "/tmp/sock"
+native_resolution = "1920x1080"
+batch_delete_size = 5
+"#,
+        )
+        .unwrap();
+        std::fs::write(
+            tmpdir.path().join("shared/small-screen.toml"),
+            "native_resolution = \"1024x768\"\nbatch_delete_size = 10\n",
+        )
+        .unwrap();
+        let frame = tmpdir.path().join("frame.toml");
+        std::fs::write(
+            &frame,
+            r#"
+include = ["shared/base.toml", "shared/small-screen.toml"]
+frame_name = "Kitchen"
+batch_delete_size = 15
+"#,
+        )
+        .unwrap();
+
+        let config = Config::from_file(&frame).unwrap();
+        assert_eq!(config.frame_name.as_deref(), Some("Kitchen"));
+        // Later includes override earlier ones; the file itself wins
+        assert_eq!(config.resolution(), (1024, 768));
+        assert_eq!(config.batch_delete_size, 15);
+
+        std::fs::write(
+            tmpdir.path().join("shared/base.toml"),
+            "include = \"../frame.toml\"\n",
+        )
+        .unwrap();
+        let err = Config::from_file(&frame).unwrap_err();
+        assert!(err.contains("cycle"), "{}", err);
+    }
+
+    #[test]
+    fn test_check_writable() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        assert!(check_writable(tmpdir.path()).is_ok());
+        assert_eq!(std::fs::read_dir(tmpdir.path()).unwrap().count(), 0);
+        assert!(check_writable(&tmpdir.path().join("missing")).is_err());
+    }
+
     #[test]
     fn test_from_file() {
         let mut tmpfile = tempfile::NamedTempFile::new().unwrap();
@@ -0,0 +1,185 @@
+// Photo Frame Manager — DRM/GBM/EGL digital photo frame.
+// Copyright (C) 2026 Daniel Mikusa <dan@mikusa.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use chrono::Utc;
+use std::backtrace::Backtrace;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::panic::PanicHookInfo;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// Crash reports are appended to this file in `log_dir`, next to the main log.
+pub const CRASH_FILE_NAME: &str = "photo-frame-crash.log";
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// A worker that stays up this long is considered healthy again, and its
+/// next restart starts from `INITIAL_BACKOFF`.
+const HEALTHY_RUN: Duration = Duration::from_secs(300);
+
+/// Last photo sent to the display, for crash reports.
+static LAST_PHOTO: Mutex<Option<String>> = Mutex::new(None);
+
+pub fn set_last_photo(path: &str) {
+    if let Ok(mut last) = LAST_PHOTO.lock() {
+        *last = Some(path.to_string());
+    }
+}
+
+/// Install a panic hook that writes a crash report (thread, message,
+/// last photo, config, backtrace) to the log and to `crash_file`.
+pub fn install_panic_hook(config_summary: String, crash_file: PathBuf) {
+    std::panic::set_hook(Box::new(move |info| {
+        let report = build_report(info, &config_summary);
+        log::error!("{}", report);
+        eprintln!("{}", report);
+        if let Err(e) = append_crash_file(&crash_file, &report) {
+            eprintln!("Failed to write {}: {}", crash_file.display(), e);
+        }
+    }));
+}
+
+fn build_report(info: &PanicHookInfo, config_summary: &str) -> String {
+    let thread = std::thread::current();
+    let message = info
+        .payload()
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| info.payload().downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic payload".to_string());
+    let location = info
+        .location()
+        .map_or_else(|| "unknown location".to_string(), |l| l.to_string());
+    // Don't block on the lock if the panic happened while holding it.
+    let last_photo = LAST_PHOTO
+        .try_lock()
+        .ok()
+        .and_then(|p| p.clone())
+        .unwrap_or_else(|| "none".to_string());
+
+    format!(
+        "Crash in thread '{}': {} at {}\nLast photo: {}\n{}\nBacktrace:\n{}",
+        thread.name().unwrap_or("unnamed"),
+        message,
+        location,
+        last_photo,
+        config_summary,
+        Backtrace::force_capture()
+    )
+}
+
+fn append_crash_file(path: &Path, report: &str) -> io::Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(
+        file,
+        "=== {} ===\n{}\n",
+        Utc::now().format("%Y-%m-%dT%H:%M:%SZ"),
+        report
+    )
+}
+
+/// Backoff before the next restart: doubles on each quick failure, capped
+/// at `MAX_BACKOFF`, and resets once the worker has run for `HEALTHY_RUN`.
+fn next_backoff(current: Duration, ran_for: Duration) -> Duration {
+    if ran_for >= HEALTHY_RUN {
+        INITIAL_BACKOFF
+    } else {
+        (current * 2).min(MAX_BACKOFF)
+    }
+}
+
+/// Run `task` on a thread named `name`, restarting it whenever it panics or
+/// returns (with an error or otherwise) before shutdown.
+pub fn supervise<F>(name: &'static str, shutdown: Arc<AtomicBool>, task: F) -> JoinHandle<()>
+where
+    F: Fn() -> io::Result<()> + Send + Sync + 'static,
+{
+    let task = Arc::new(task);
+    std::thread::spawn(move || {
+        let mut backoff = INITIAL_BACKOFF;
+        loop {
+            let started = Instant::now();
+            let worker = task.clone();
+            let result = std::thread::Builder::new()
+                .name(name.to_string())
+                .spawn(move || worker())
+                .map(|handle| handle.join());
+
+            if shutdown.load(Ordering::Relaxed) {
+                return;
+            }
+            match result {
+                Ok(Ok(Ok(()))) => log::warn!("{} thread exited unexpectedly", name),
+                Ok(Ok(Err(e))) => log::error!("{} thread failed: {}", name, e),
+                Ok(Err(_)) => log::error!("{} thread panicked", name),
+                Err(e) => log::error!("Failed to spawn {} thread: {}", name, e),
+            }
+
+            backoff = next_backoff(backoff, started.elapsed());
+            log::info!("Restarting {} thread in {}s", name, backoff.as_secs());
+            let resume = Instant::now() + backoff;
+            while Instant::now() < resume {
+                if shutdown.load(Ordering::Relaxed) {
+                    return;
+                }
+                std::thread::sleep(Duration::from_millis(100));
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    #[test]
+    fn test_next_backoff() {
+        assert_eq!(
+            next_backoff(Duration::from_secs(1), Duration::ZERO),
+            Duration::from_secs(2)
+        );
+        assert_eq!(next_backoff(MAX_BACKOFF, Duration::ZERO), MAX_BACKOFF);
+        assert_eq!(
+            next_backoff(Duration::from_secs(32), HEALTHY_RUN),
+            INITIAL_BACKOFF
+        );
+    }
+
+    #[test]
+    fn test_supervise_restarts_failed_task() {
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let runs = Arc::new(AtomicUsize::new(0));
+
+        let task_runs = runs.clone();
+        let task_shutdown = shutdown.clone();
+        let handle = supervise("test", shutdown.clone(), move || {
+            if task_runs.fetch_add(1, Ordering::SeqCst) == 0 {
+                return Err(io::Error::other("first run fails"));
+            }
+            task_shutdown.store(true, Ordering::SeqCst);
+            Ok(())
+        });
+
+        handle.join().unwrap();
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+    }
+}
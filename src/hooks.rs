@@ -0,0 +1,258 @@
+// Photo Frame Manager — DRM/GBM/EGL digital photo frame.
+// Copyright (C) 2026 Daniel Mikusa <dan@mikusa.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::config::Config;
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::sync::OnceLock;
+use std::thread;
+
+/// Lifecycle events passed to the hook script in `PHOTO_FRAME_EVENT`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HookEvent {
+    /// A single photo was converted and appended to the index.
+    PhotoImported,
+    /// A directory import (USB drive or `--import-dir`) finished.
+    ImportComplete,
+    /// Old photos were deleted to free space.
+    PhotosRotated,
+    /// The system monitor saw memory use growing faster than
+    /// `memory_growth_warning`.
+    MemoryGrowth,
+}
+
+impl HookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HookEvent::PhotoImported => "photo_imported",
+            HookEvent::ImportComplete => "import_complete",
+            HookEvent::PhotosRotated => "photos_rotated",
+            HookEvent::MemoryGrowth => "memory_growth",
+        }
+    }
+}
+
+/// Hook runs waiting for the one in progress. Events past this are dropped.
+const HOOK_QUEUE_LEN: usize = 32;
+
+static RUNNER: OnceLock<HookRunner> = OnceLock::new();
+
+struct HookJob {
+    script: PathBuf,
+    event: HookEvent,
+    cmd: Command,
+}
+
+/// Runs queued hooks one at a time on a background thread, so a burst of
+/// events during a large import cannot fork a process per photo at once.
+struct HookRunner {
+    tx: SyncSender<HookJob>,
+}
+
+impl HookRunner {
+    fn new(queue_len: usize) -> Self {
+        let (tx, rx) = mpsc::sync_channel::<HookJob>(queue_len);
+        thread::spawn(move || {
+            for job in rx {
+                run_job(job);
+            }
+        });
+        HookRunner { tx }
+    }
+
+    /// Queue `job` without blocking. Returns false if it was dropped.
+    fn submit(&self, job: HookJob) -> bool {
+        match self.tx.try_send(job) {
+            Ok(()) => true,
+            Err(TrySendError::Full(job)) => {
+                log::warn!(
+                    "Hook {} is still busy, dropping {} event",
+                    job.script.display(),
+                    job.event.as_str()
+                );
+                false
+            }
+            Err(TrySendError::Disconnected(job)) => {
+                log::warn!("Hook runner stopped, dropping {} event", job.event.as_str());
+                false
+            }
+        }
+    }
+}
+
+fn run_job(mut job: HookJob) {
+    let status = match job.cmd.spawn() {
+        Ok(mut child) => child.wait(),
+        Err(e) => {
+            log::warn!("Failed to run hook {}: {}", job.script.display(), e);
+            return;
+        }
+    };
+    match status {
+        Ok(status) if !status.success() => {
+            log::warn!(
+                "Hook {} ({}) exited with {}",
+                job.script.display(),
+                job.event.as_str(),
+                status
+            );
+        }
+        Ok(_) => {}
+        Err(e) => log::warn!("Failed to wait for hook {}: {}", job.script.display(), e),
+    }
+}
+
+/// Run the hook script for `event`, if one is configured.
+///
+/// The script runs in the background at low priority so a slow script never
+/// stalls an import. Runs are queued and never overlap. Failures are logged
+/// and otherwise ignored.
+pub fn run_hook(config: &Config, event: HookEvent, vars: &[(&str, String)]) {
+    let Some(script) = config.hook_script.as_deref() else {
+        return;
+    };
+
+    let job = HookJob {
+        script: script.to_path_buf(),
+        event,
+        cmd: hook_command(script, config.frame_name.as_deref(), event, vars),
+    };
+    RUNNER
+        .get_or_init(|| HookRunner::new(HOOK_QUEUE_LEN))
+        .submit(job);
+}
+
+fn hook_command(
+    script: &Path,
+    frame_name: Option<&str>,
+    event: HookEvent,
+    vars: &[(&str, String)],
+) -> Command {
+    let mut cmd = Command::new(script);
+    if let Some(name) = frame_name {
+        cmd.env("PHOTO_FRAME_NAME", name);
+    }
+    cmd.env("PHOTO_FRAME_EVENT", event.as_str())
+        .envs(vars.iter().map(|(k, v)| (k, v)))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+
+    // SAFETY: the closure runs in the forked child before exec, where only
+    // async-signal-safe calls are allowed. It calls nothing but `nice`, which
+    // is a plain syscall, and it does not allocate or take locks.
+    unsafe {
+        cmd.pre_exec(|| {
+            libc::nice(10);
+            Ok(())
+        });
+    }
+
+    cmd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn test_hook_command_passes_event_and_vars() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let out = tmpdir.path().join("out.txt");
+        let script = tmpdir.path().join("hook.sh");
+        fs::write(
+            &script,
+            format!(
+                "#!/bin/sh\necho \"$PHOTO_FRAME_NAME $PHOTO_FRAME_EVENT $PHOTO_FRAME_PHOTO_PATH\" > {}\n",
+                out.display()
+            ),
+        )
+        .unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let mut child = hook_command(
+            &script,
+            Some("kitchen"),
+            HookEvent::PhotoImported,
+            &[("PHOTO_FRAME_PHOTO_PATH", "/photos/a.jpg".to_string())],
+        )
+        .spawn()
+        .unwrap();
+        assert!(child.wait().unwrap().success());
+
+        let contents = fs::read_to_string(&out).unwrap();
+        assert_eq!(contents.trim(), "kitchen photo_imported /photos/a.jpg");
+    }
+
+    fn mkfifo(path: &Path) {
+        let c_path = std::ffi::CString::new(path.as_os_str().as_encoded_bytes()).unwrap();
+        assert_eq!(unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) }, 0);
+    }
+
+    #[test]
+    fn test_hook_runner_runs_one_at_a_time() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let out = tmpdir.path().join("out.txt");
+        // The script reports on `signal` when it starts and ends, and waits
+        // on `gate` in between until the test lets it finish.
+        let signal = tmpdir.path().join("signal");
+        let gate = tmpdir.path().join("gate");
+        mkfifo(&signal);
+        mkfifo(&gate);
+        let script = tmpdir.path().join("hook.sh");
+        fs::write(
+            &script,
+            format!(
+                "#!/bin/sh\necho start >> {0}\necho > {1}\nread _ < {2}\necho end >> {0}\necho > {1}\n",
+                out.display(),
+                signal.display(),
+                gate.display()
+            ),
+        )
+        .unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+        let job = || HookJob {
+            script: script.clone(),
+            event: HookEvent::PhotoImported,
+            cmd: hook_command(&script, None, HookEvent::PhotoImported, &[]),
+        };
+        let wait_for_signal = || fs::read_to_string(&signal).unwrap();
+        let release = || fs::write(&gate, "\n").unwrap();
+        let output = || fs::read_to_string(&out).unwrap();
+
+        let runner = HookRunner::new(1);
+        assert!(runner.submit(job()));
+        wait_for_signal();
+
+        // One run in progress and one queued: the next is dropped
+        assert!(runner.submit(job()));
+        assert!(!runner.submit(job()));
+
+        release();
+        wait_for_signal();
+        // The queued run starts only after the first has finished
+        wait_for_signal();
+        assert_eq!(output(), "start\nend\nstart\n");
+
+        release();
+        wait_for_signal();
+        assert_eq!(output(), "start\nend\nstart\nend\n");
+    }
+}
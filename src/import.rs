@@ -15,15 +15,20 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use crate::config::{AspectRatioMode, Config};
-use crate::index::{self, IndexWriter};
+use crate::hooks::{self, HookEvent};
+use crate::index::{self, IndexMetadata, IndexWriter};
+use crate::takeout;
 use crc32fast::Hasher;
 use notify::{Config as NotifyConfig, Event, RecommendedWatcher, RecursiveMode, Watcher};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::ffi::CString;
 use std::fs;
 use std::io::{self, Read};
+use std::os::unix::ffi::OsStrExt;
 use std::os::unix::process::CommandExt;
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
@@ -33,7 +38,7 @@ pub fn watch_usb_mounts(
     index_dir: PathBuf,
     dedup_set: Arc<Mutex<HashSet<u64>>>,
     config: Config,
-    shutdown: Arc<std::sync::atomic::AtomicBool>,
+    shutdown: Arc<AtomicBool>,
 ) -> io::Result<()> {
     let (tx, rx) = std::sync::mpsc::channel();
     let mut watcher: RecommendedWatcher = Watcher::new(
@@ -55,7 +60,7 @@ pub fn watch_usb_mounts(
     let mut active_mounts: HashSet<PathBuf> = HashSet::new();
 
     loop {
-        if shutdown.load(std::sync::atomic::Ordering::Relaxed) {
+        if shutdown.load(Ordering::Relaxed) {
             log::info!("USB watcher shutting down");
             break;
         }
@@ -108,7 +113,118 @@ pub fn watch_usb_mounts(
     Ok(())
 }
 
-/// Import all JPEGs from a directory (USB mount or local folder).
+/// A file must go this long without being modified before it is taken from
+/// the inbox, so files still being copied in (SFTP, Syncthing) are left alone.
+const INBOX_SETTLE_TIME: Duration = Duration::from_secs(10);
+
+/// How often the inbox is scanned for new files.
+const INBOX_SCAN_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Import photos dropped into `inbox_dir`, removing each one once it is in
+/// the library. Files already in the inbox at startup are picked up on the
+/// first scan.
+pub fn watch_inbox(
+    inbox_dir: PathBuf,
+    photos_dir: PathBuf,
+    index_dir: PathBuf,
+    dedup_set: Arc<Mutex<HashSet<u64>>>,
+    config: Config,
+    shutdown: Arc<AtomicBool>,
+) -> io::Result<()> {
+    let importer = Importer {
+        photos_dir: &photos_dir,
+        index_dir: &index_dir,
+        dedup_set: &dedup_set,
+        config: &config,
+        magick: magick_command()?,
+    };
+    log::info!("Watching {} for new photos", inbox_dir.display());
+
+    let mut failed = HashMap::new();
+    loop {
+        scan_inbox(&inbox_dir, &importer, &mut failed, SystemTime::now());
+
+        let mut waited = Duration::ZERO;
+        while waited < INBOX_SCAN_INTERVAL {
+            if shutdown.load(Ordering::Relaxed) {
+                log::info!("Inbox watcher shutting down");
+                return Ok(());
+            }
+            std::thread::sleep(Duration::from_millis(100));
+            waited += Duration::from_millis(100);
+        }
+    }
+}
+
+/// Import every settled image in the inbox and remove it, duplicates and
+/// exposure rejects included. Files that fail to import are left in place and recorded in
+/// `failed` with their mtime, so they are retried only once they change.
+/// Returns the number of photos imported.
+fn scan_inbox(
+    inbox_dir: &Path,
+    importer: &Importer,
+    failed: &mut HashMap<PathBuf, SystemTime>,
+    now: SystemTime,
+) -> usize {
+    failed.retain(|path, _| path.exists());
+
+    let mut imported = 0;
+    let mut skipped = 0;
+    let mut rejected = 0;
+    for photo_path in find_images(inbox_dir) {
+        let Ok(mtime) = fs::metadata(&photo_path).and_then(|m| m.modified()) else {
+            continue;
+        };
+        if now.duration_since(mtime).unwrap_or_default() < INBOX_SETTLE_TIME
+            || failed.get(&photo_path) == Some(&mtime)
+        {
+            continue;
+        }
+
+        match import_single_photo(&photo_path, importer) {
+            Ok(outcome) => {
+                match outcome {
+                    ImportOutcome::Imported => imported += 1,
+                    ImportOutcome::Duplicate => skipped += 1,
+                    ImportOutcome::Rejected => rejected += 1,
+                }
+                if let Err(e) = fs::remove_file(&photo_path) {
+                    log::warn!(
+                        "Failed to remove {} from inbox: {}",
+                        photo_path.display(),
+                        e
+                    );
+                }
+            }
+            Err(e) => {
+                log::warn!("Failed to import {}: {}", photo_path.display(), e);
+                failed.insert(photo_path, mtime);
+            }
+        }
+    }
+
+    if imported > 0 || skipped > 0 || rejected > 0 {
+        log::info!(
+            "Inbox import: {} imported, {} skipped (duplicates), {} rejected (exposure)",
+            imported,
+            skipped,
+            rejected
+        );
+        hooks::run_hook(
+            importer.config,
+            HookEvent::ImportComplete,
+            &[
+                ("PHOTO_FRAME_IMPORT_DIR", inbox_dir.display().to_string()),
+                ("PHOTO_FRAME_IMPORTED", imported.to_string()),
+                ("PHOTO_FRAME_SKIPPED", skipped.to_string()),
+                ("PHOTO_FRAME_REJECTED", rejected.to_string()),
+            ],
+        );
+    }
+    imported
+}
+
+/// Import all supported images from a directory (USB mount or local folder).
 pub fn import_from_directory(
     dir: &Path,
     photos_dir: &Path,
@@ -116,17 +232,30 @@ pub fn import_from_directory(
     dedup_set: &Arc<Mutex<HashSet<u64>>>,
     config: &Config,
 ) -> io::Result<()> {
+    let importer = Importer {
+        photos_dir,
+        index_dir,
+        dedup_set,
+        config,
+        magick: magick_command()?,
+    };
+    import_directory(dir, &importer)
+}
+
+fn import_directory(dir: &Path, importer: &Importer) -> io::Result<()> {
     // Resolve to an absolute path so downstream syscalls are not affected
     // by the process's current working directory.
     let abs_dir = dir.canonicalize()?;
     let images = find_images(&abs_dir);
     let mut imported = 0;
     let mut skipped = 0;
+    let mut rejected = 0;
 
     for photo_path in images {
-        match import_single_photo(&photo_path, photos_dir, index_dir, dedup_set, config) {
-            Ok(true) => imported += 1,
-            Ok(false) => skipped += 1,
+        match import_single_photo(&photo_path, importer) {
+            Ok(ImportOutcome::Imported) => imported += 1,
+            Ok(ImportOutcome::Duplicate) => skipped += 1,
+            Ok(ImportOutcome::Rejected) => rejected += 1,
             Err(e) => {
                 log::warn!("Failed to import {}: {}", photo_path.display(), e);
             }
@@ -134,10 +263,21 @@ pub fn import_from_directory(
     }
 
     log::info!(
-        "Import summary from {}: {} imported, {} skipped (duplicates)",
+        "Import summary from {}: {} imported, {} skipped (duplicates), {} rejected (exposure)",
         abs_dir.display(),
         imported,
-        skipped
+        skipped,
+        rejected
+    );
+    hooks::run_hook(
+        importer.config,
+        HookEvent::ImportComplete,
+        &[
+            ("PHOTO_FRAME_IMPORT_DIR", abs_dir.display().to_string()),
+            ("PHOTO_FRAME_IMPORTED", imported.to_string()),
+            ("PHOTO_FRAME_SKIPPED", skipped.to_string()),
+            ("PHOTO_FRAME_REJECTED", rejected.to_string()),
+        ],
     );
     Ok(())
 }
@@ -153,51 +293,115 @@ fn import_from_mount(
     import_from_directory(mount_point, photos_dir, index_dir, &dedup_set, config)
 }
 
-const IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "heif", "heifs", "heic", "heics"];
+const IMAGE_EXTENSIONS: &[&str] = &[
+    "jpg", "jpeg", "heif", "heifs", "heic", "heics", "pdf", "svg",
+];
 
-/// Find all image files under a directory, recursively.
+/// Vector formats that ImageMagick rasterizes rather than decodes.
+const VECTOR_EXTENSIONS: &[&str] = &["pdf", "svg"];
+
+/// Rasterization density (DPI) for vector formats. High enough that an A4 page
+/// comes out taller than 1080p before it is resized down.
+const VECTOR_DENSITY: &str = "200";
+
+/// Find all image files under a directory, recursively. Unreadable
+/// directories and entries that vanish mid-scan (a drive pulled, a sync tool
+/// renaming files) are logged and skipped, so the rest still get imported.
 fn find_images(dir: &Path) -> Vec<PathBuf> {
     let mut result = Vec::new();
-    if let Ok(entries) = fs::read_dir(dir) {
-        for entry in entries.filter_map(|e| e.ok()) {
-            let path = entry.path();
-            if path.is_dir() {
-                result.extend(find_images(&path));
-            } else if let Some(ext) = path.extension() {
-                let ext = ext.to_string_lossy().to_lowercase();
-                if IMAGE_EXTENSIONS.contains(&ext.as_ref()) {
-                    result.push(path);
-                }
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            log::warn!("Skipping {}: {}", dir.display(), e);
+            return result;
+        }
+    };
+    for entry in entries {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                log::warn!("Skipping entry in {}: {}", dir.display(), e);
+                continue;
+            }
+        };
+        let path = entry.path();
+        if path.is_dir() {
+            result.extend(find_images(&path));
+        } else if let Some(ext) = path.extension() {
+            let ext = ext.to_string_lossy().to_lowercase();
+            if IMAGE_EXTENSIONS.contains(&ext.as_ref()) {
+                result.push(path);
             }
         }
     }
     result
 }
 
-/// Import a single photo. Returns Ok(true) if imported, Ok(false) if skipped (duplicate).
-fn import_single_photo(
-    src_path: &Path,
-    photos_dir: &Path,
-    index_dir: &Path,
-    dedup_set: &Arc<Mutex<HashSet<u64>>>,
-    config: &Config,
-) -> io::Result<bool> {
+/// Everything an import needs besides the photo itself.
+struct Importer<'a> {
+    photos_dir: &'a Path,
+    index_dir: &'a Path,
+    /// Hashes already in the library. Doubles as the import lock.
+    dedup_set: &'a Arc<Mutex<HashSet<u64>>>,
+    config: &'a Config,
+    /// ImageMagick command, looked up once per import run.
+    magick: &'a str,
+}
+
+/// What happened to a photo that was not an import error.
+#[derive(Debug, PartialEq)]
+enum ImportOutcome {
+    Imported,
+    /// Already in the library.
+    Duplicate,
+    /// Converted, then thrown away by `skip_bad_exposure`. Its hash is kept
+    /// in the dedup set so it is not converted again while the manager runs.
+    Rejected,
+}
+
+/// Import a single photo.
+///
+/// `importer.dedup_set` doubles as the import lock. The USB and inbox watchers run at
+/// the same time, and the index tracks its count by renaming the file, so it
+/// is held from the duplicate check until the hash is recorded.
+fn import_single_photo(src_path: &Path, importer: &Importer) -> io::Result<ImportOutcome> {
+    let &Importer {
+        photos_dir,
+        index_dir,
+        dedup_set,
+        config,
+        magick,
+    } = importer;
+
+    // Reject oversized files before reading them
+    let size = fs::metadata(src_path)?.len();
+    if config.max_import_size > 0 && size > config.max_import_size {
+        return Err(io::Error::other(format!(
+            "File is {} bytes, over max_import_size ({} bytes)",
+            size, config.max_import_size
+        )));
+    }
+
     // Compute hash
     let hash = compute_file_hash(src_path)?;
 
     // Check deduplication
-    {
-        let set = dedup_set.lock().unwrap();
-        if set.contains(&hash) {
-            log::debug!("Skipping duplicate: {}", src_path.display());
-            return Ok(false);
-        }
+    // A panic mid-import poisons the lock. The set is still usable (at worst
+    // it misses that one hash), and the supervisor restarts the watcher, so
+    // recover rather than fail every import from then on.
+    let mut set = dedup_set.lock().unwrap_or_else(|e| e.into_inner());
+    if set.contains(&hash) {
+        log::debug!("Skipping duplicate: {}", src_path.display());
+        return Ok(ImportOutcome::Duplicate);
     }
 
-    // Determine destination path based on file mtime
-    let mtime = fs::metadata(src_path)?
-        .modified()
-        .unwrap_or(SystemTime::now());
+    // Determine destination path from the Takeout capture time, else file mtime
+    let mtime = match takeout::taken_time(src_path) {
+        Some(taken) => taken,
+        None => fs::metadata(src_path)?
+            .modified()
+            .unwrap_or(SystemTime::now()),
+    };
     let dest_path = build_dest_path(src_path, photos_dir, mtime);
 
     // Ensure parent directory exists
@@ -205,21 +409,29 @@ fn import_single_photo(
         fs::create_dir_all(parent)?;
     }
 
+    ensure_free_space(photos_dir, index_dir, config)?;
+
     // Convert and copy
     let (width, height) = config.resolution();
     let mode = &config.aspect_ratio_mode;
-    match convert_image(src_path, &dest_path, width, height, mode) {
+    let strip = config.strip_metadata;
+    match convert_image(magick, src_path, &dest_path, width, height, mode, strip) {
         Ok(()) => {}
         Err(e) => {
             // If ENOSPC, try to free space and retry once
-            if e.kind() == io::ErrorKind::WriteZero {
+            if matches!(
+                e.kind(),
+                io::ErrorKind::WriteZero | io::ErrorKind::StorageFull
+            ) {
                 log::warn!("Disk full, attempting rotation");
                 let (_index_path, meta) = index::init_index(index_dir)?;
-                let (_new_meta, deleted) =
-                    index::delete_oldest(index_dir, &meta, config.batch_delete_size)?;
-                log::info!("Deleted {} old photos to free space", deleted);
+                if rotate_oldest(index_dir, &meta, config)? == 0 {
+                    return Err(e);
+                }
                 // Retry the conversion
-                if let Err(e2) = convert_image(src_path, &dest_path, width, height, mode) {
+                if let Err(e2) =
+                    convert_image(magick, src_path, &dest_path, width, height, mode, strip)
+                {
                     return Err(io::Error::other(format!(
                         "Conversion failed after rotation: {}",
                         e2
@@ -230,6 +442,19 @@ fn import_single_photo(
             }
         }
     }
+    if config.skip_bad_exposure && is_bad_exposure(magick, src_path, &dest_path) {
+        set.insert(hash);
+        return Ok(ImportOutcome::Rejected);
+    }
+
+    // Carry the capture time over so the library's mtimes match its dates
+    if let Err(e) = fs::File::options()
+        .write(true)
+        .open(&dest_path)
+        .and_then(|f| f.set_modified(mtime))
+    {
+        log::warn!("Failed to set mtime on {}: {}", dest_path.display(), e);
+    }
 
     // Append to index
     let original_name = src_path
@@ -243,10 +468,8 @@ fn import_single_photo(
     writer.sync_metadata()?;
 
     // Add to dedup set
-    {
-        let mut set = dedup_set.lock().unwrap();
-        set.insert(hash);
-    }
+    set.insert(hash);
+    drop(set);
 
     log::info!(
         "Imported {} -> {} (line {})",
@@ -254,8 +477,123 @@ fn import_single_photo(
         dest_path.display(),
         line_number
     );
+    hooks::run_hook(
+        config,
+        HookEvent::PhotoImported,
+        &[
+            ("PHOTO_FRAME_PHOTO_PATH", dest_path.display().to_string()),
+            ("PHOTO_FRAME_SOURCE_PATH", src_path.display().to_string()),
+        ],
+    );
 
-    Ok(true)
+    Ok(ImportOutcome::Imported)
+}
+
+/// Bytes available to unprivileged users on the filesystem holding `path`.
+pub fn free_space(path: &Path) -> io::Result<u64> {
+    let c_path = CString::new(path.as_os_str().as_bytes())?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+/// Delete up to `batch_delete_size` of the oldest photos, never going below
+/// `min_photos_kept`, and run the rotation hook. Returns how many were deleted.
+fn rotate_oldest(index_dir: &Path, meta: &IndexMetadata, config: &Config) -> io::Result<usize> {
+    let count = config
+        .batch_delete_size
+        .min(meta.valid_count.saturating_sub(config.min_photos_kept));
+    if count == 0 {
+        log::warn!(
+            "Only {} photos left (min_photos_kept = {}), not rotating any out",
+            meta.valid_count,
+            config.min_photos_kept
+        );
+        return Ok(0);
+    }
+    let (_new_meta, deleted) = index::delete_oldest(index_dir, meta, count)?;
+    log::info!("Deleted {} old photos to free space", deleted);
+    hooks::run_hook(
+        config,
+        HookEvent::PhotosRotated,
+        &[("PHOTO_FRAME_DELETED", deleted.to_string())],
+    );
+    Ok(deleted)
+}
+
+/// Set once a rotation failed to bring free space back up to
+/// `min_free_space`. Until free space recovers, imports are skipped without
+/// rotating, so one large import cannot empty the library batch by batch.
+static ROTATION_STALLED: AtomicBool = AtomicBool::new(false);
+
+/// Make sure at least `min_free_space` bytes are free on the photos volume
+/// before an import, rotating out at most one batch of old photos.
+fn ensure_free_space(photos_dir: &Path, index_dir: &Path, config: &Config) -> io::Result<()> {
+    reserve_free_space(photos_dir, index_dir, config, &ROTATION_STALLED, free_space)
+}
+
+fn reserve_free_space(
+    photos_dir: &Path,
+    index_dir: &Path,
+    config: &Config,
+    stalled: &AtomicBool,
+    free_space: impl Fn(&Path) -> io::Result<u64>,
+) -> io::Result<()> {
+    if config.min_free_space == 0 {
+        return Ok(());
+    }
+    let mut free = free_space(photos_dir)?;
+    if free >= config.min_free_space {
+        if stalled.swap(false, Ordering::Relaxed) {
+            log::info!(
+                "Free space on {} is back above min_free_space, rotation resumed",
+                photos_dir.display()
+            );
+        }
+        return Ok(());
+    }
+    if !stalled.load(Ordering::Relaxed) {
+        log::warn!(
+            "{} bytes free on {}, below min_free_space ({}), attempting rotation",
+            free,
+            photos_dir.display(),
+            config.min_free_space
+        );
+        let (_index_path, meta) = index::init_index(index_dir)?;
+        if rotate_oldest(index_dir, &meta, config)? > 0 {
+            let after = free_space(photos_dir)?;
+            if after >= config.min_free_space {
+                return Ok(());
+            }
+            if after <= free {
+                log::warn!(
+                    "Rotating out photos freed no space on {}, something else is filling it",
+                    photos_dir.display()
+                );
+            }
+            free = after;
+        }
+        log::warn!(
+            "Not rotating again until free space on {} recovers",
+            photos_dir.display()
+        );
+        stalled.store(true, Ordering::Relaxed);
+    }
+    log::warn!(
+        "{} bytes free on {}, below min_free_space ({}), skipping import",
+        free,
+        photos_dir.display(),
+        config.min_free_space
+    );
+    Err(io::Error::new(
+        io::ErrorKind::StorageFull,
+        format!(
+            "Skipped, {} bytes free, below min_free_space ({})",
+            free, config.min_free_space
+        ),
+    ))
 }
 
 /// Compute a fast hash of the first 32KB + file size.
@@ -274,7 +612,20 @@ fn compute_file_hash(path: &Path) -> io::Result<u64> {
     Ok(hasher.finalize() as u64)
 }
 
+/// Returns the lowercased extension of `path`, if any.
+fn lowercase_extension(path: &Path) -> Option<String> {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+}
+
+fn is_vector_image(path: &Path) -> bool {
+    lowercase_extension(path).is_some_and(|ext| VECTOR_EXTENSIONS.contains(&ext.as_str()))
+}
+
 /// Build the destination path: photos_dir/YYYY/MM/DD/DDDDD_original_name.jpg
+///
+/// Non-JPEG sources get a `.jpg` extension, since ImageMagick picks the output
+/// format from it and the display app only decodes JPEG.
 fn build_dest_path(src_path: &Path, photos_dir: &Path, mtime: SystemTime) -> PathBuf {
     let duration = mtime.duration_since(UNIX_EPOCH).unwrap_or_default();
     let datetime = chrono::DateTime::from_timestamp(duration.as_secs() as i64, 0)
@@ -284,11 +635,14 @@ fn build_dest_path(src_path: &Path, photos_dir: &Path, mtime: SystemTime) -> Pat
     let month = datetime.format("%m").to_string();
     let day = datetime.format("%d").to_string();
 
-    let original_name = src_path
-        .file_name()
-        .unwrap_or_default()
-        .to_string_lossy()
-        .to_string();
+    let mut original_name = PathBuf::from(src_path.file_name().unwrap_or_default());
+    if !matches!(
+        lowercase_extension(src_path).as_deref(),
+        Some("jpg" | "jpeg")
+    ) {
+        original_name.set_extension("jpg");
+    }
+    let original_name = original_name.to_string_lossy().to_string();
 
     // For now, use a timestamp-based sequence number since we don't know the CSV line yet
     // The actual sequence number will be assigned after CSV append
@@ -305,26 +659,51 @@ fn build_dest_path(src_path: &Path, photos_dir: &Path, mtime: SystemTime) -> Pat
         .join(format!("{}_{}", seq_str, original_name))
 }
 
+/// The input argument for ImageMagick. PDFs are limited to their first page,
+/// otherwise ImageMagick writes one output file per page.
+fn magick_input_arg(src: &Path) -> std::ffi::OsString {
+    let mut arg = src.as_os_str().to_os_string();
+    if lowercase_extension(src).as_deref() == Some("pdf") {
+        arg.push("[0]");
+    }
+    arg
+}
+
+/// ImageMagick 7 installs `magick`; older versions only have `convert`.
+fn magick_command() -> io::Result<&'static str> {
+    if Command::new("magick").arg("--version").output().is_ok() {
+        Ok("magick")
+    } else if Command::new("convert").arg("--version").output().is_ok() {
+        Ok("convert")
+    } else {
+        Err(io::Error::other(
+            "ImageMagick not found in PATH (tried 'magick' and 'convert')",
+        ))
+    }
+}
+
 /// Convert an image using ImageMagick.
+///
+/// With `strip_metadata`, EXIF and other profiles are removed from the output.
+/// The image is auto-oriented first, since the display app has no other way to
+/// learn about the EXIF orientation once the tag is gone.
 fn convert_image(
+    magick: &str,
     src: &Path,
     dest: &Path,
     width: u32,
     height: u32,
     mode: &AspectRatioMode,
+    strip_metadata: bool,
 ) -> io::Result<()> {
-    let magick_cmd = if Command::new("magick").arg("--version").output().is_ok() {
-        "magick"
-    } else if Command::new("convert").arg("--version").output().is_ok() {
-        "convert"
-    } else {
-        return Err(io::Error::other(
-            "ImageMagick not found in PATH (tried 'magick' and 'convert')",
-        ));
-    };
-
-    let mut cmd = Command::new(magick_cmd);
-    cmd.arg(src);
+    let mut cmd = Command::new(magick);
+    if is_vector_image(src) {
+        cmd.arg("-density").arg(VECTOR_DENSITY);
+    }
+    cmd.arg(magick_input_arg(src));
+    if strip_metadata {
+        cmd.arg("-auto-orient").arg("-strip");
+    }
     if matches!(mode, AspectRatioMode::Fill) {
         cmd.arg("-resize")
             .arg(format!("{}x{}^", width, height))
@@ -337,6 +716,9 @@ fn convert_image(
     }
     cmd.arg(dest);
 
+    // SAFETY: the closure runs in the forked child before exec, where only
+    // async-signal-safe calls are allowed. It calls nothing but `nice`, which
+    // is a plain syscall, and it does not allocate or take locks.
     unsafe {
         cmd.pre_exec(|| {
             libc::nice(10);
@@ -348,17 +730,256 @@ fn convert_image(
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(io::Error::other(format!("ImageMagick failed: {}", stderr)));
+        // ImageMagick reports a full disk as an ordinary failure; surface it
+        // as ENOSPC so the caller can rotate and retry.
+        let kind = if stderr.contains("No space left on device") {
+            io::ErrorKind::StorageFull
+        } else {
+            io::ErrorKind::Other
+        };
+        return Err(io::Error::new(
+            kind,
+            format!("ImageMagick failed: {}", stderr),
+        ));
     }
 
     Ok(())
 }
 
+/// Mean luminance at or below this (0-1) is treated as a nearly black frame.
+const TOO_DARK_MEAN: f64 = 0.05;
+/// Mean luminance at or above this is treated as blown out.
+const BLOWN_OUT_MEAN: f64 = 0.95;
+/// Luminance standard deviation below this is treated as a featureless frame.
+const LOW_CONTRAST_STDDEV: f64 = 0.03;
+
+#[derive(Debug, PartialEq)]
+enum ExposureProblem {
+    TooDark,
+    BlownOut,
+    LowContrast,
+}
+
+impl std::fmt::Display for ExposureProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExposureProblem::TooDark => write!(f, "too dark"),
+            ExposureProblem::BlownOut => write!(f, "blown out"),
+            ExposureProblem::LowContrast => write!(f, "too little contrast"),
+        }
+    }
+}
+
+/// Classify a photo by the mean and standard deviation of its luminance,
+/// both 0-1. Returns `None` for a usable photo.
+fn classify_exposure(mean: f64, stddev: f64) -> Option<ExposureProblem> {
+    if mean <= TOO_DARK_MEAN {
+        Some(ExposureProblem::TooDark)
+    } else if mean >= BLOWN_OUT_MEAN {
+        Some(ExposureProblem::BlownOut)
+    } else if stddev < LOW_CONTRAST_STDDEV {
+        Some(ExposureProblem::LowContrast)
+    } else {
+        None
+    }
+}
+
+/// Parse ImageMagick's `"<mean> <stddev>"` output.
+fn parse_exposure(output: &str) -> Option<(f64, f64)> {
+    let mut parts = output.split_whitespace();
+    let mean = parts.next()?.parse().ok()?;
+    let stddev = parts.next()?.parse().ok()?;
+    Some((mean, stddev))
+}
+
+/// Luminance mean and standard deviation of an image. Run on the converted
+/// copy, which is already down to screen size and cheap to decode again.
+fn measure_exposure(magick: &str, path: &Path) -> io::Result<(f64, f64)> {
+    let output = Command::new(magick)
+        .arg(path)
+        .arg("-colorspace")
+        .arg("Gray")
+        .arg("-format")
+        .arg("%[fx:mean] %[fx:standard_deviation]")
+        .arg("info:")
+        .output()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "ImageMagick failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+    parse_exposure(&stdout)
+        .ok_or_else(|| io::Error::other(format!("Unexpected ImageMagick output: {}", stdout)))
+}
+
+/// With `skip_bad_exposure`, check whether a converted photo is nearly black,
+/// blown out, or featureless. If so, remove it and return true.
+fn is_bad_exposure(magick: &str, src: &Path, dest: &Path) -> bool {
+    let (mean, stddev) = match measure_exposure(magick, dest) {
+        Ok(m) => m,
+        Err(e) => {
+            // Not worth losing the photo over
+            log::warn!("Could not check exposure of {}: {}", src.display(), e);
+            return false;
+        }
+    };
+    let Some(problem) = classify_exposure(mean, stddev) else {
+        return false;
+    };
+    log::info!(
+        "Skipped {}: {} (mean {:.3}, stddev {:.3})",
+        src.display(),
+        problem,
+        mean,
+        stddev
+    );
+    let _ = fs::remove_file(dest);
+    true
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::fs::File;
     use std::io::Write;
+    use std::os::unix::fs::PermissionsExt;
+
+    /// Stand-in for ImageMagick, so imports run without it. Conversions copy
+    /// the input and append its path to `magick.log` next to it. Exposure
+    /// checks report a nearly black image for files containing "dark". The
+    /// script is removed when this is dropped.
+    struct FakeMagick {
+        _dir: tempfile::TempDir,
+        command: String,
+    }
+
+    impl FakeMagick {
+        fn new() -> Self {
+            let dir = tempfile::tempdir().unwrap();
+            let script = dir.path().join("magick");
+            fs::write(
+                &script,
+                r#"#!/bin/sh
+[ "$1" = "--version" ] && exit 0
+for last in "$@"; do :; done
+if [ "$last" = "info:" ]; then
+    if grep -q dark "$1"; then echo "0.01 0.001"; else echo "0.5 0.2"; fi
+    exit 0
+fi
+echo "$1" >> "$(dirname "$1")/magick.log"
+cp "$1" "$last"
+"#,
+            )
+            .unwrap();
+            fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+            let command = script.to_string_lossy().to_string();
+            FakeMagick { _dir: dir, command }
+        }
+    }
+
+    /// An importer for a library that keeps its index next to the photos.
+    fn test_importer<'a>(
+        photos_dir: &'a Path,
+        dedup_set: &'a Arc<Mutex<HashSet<u64>>>,
+        config: &'a Config,
+        magick: &'a FakeMagick,
+    ) -> Importer<'a> {
+        Importer {
+            photos_dir,
+            index_dir: photos_dir,
+            dedup_set,
+            config,
+            magick: &magick.command,
+        }
+    }
+
+    fn test_config(photos_dir: &Path, extra: &str) -> Config {
+        toml::from_str(&format!(
+            "photos_dir = \"{}\"\nsocket_path = \"/tmp/sock\"\nnative_resolution = \"1920x1080\"\n{}",
+            photos_dir.display(),
+            extra
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_concurrent_imports_share_one_index() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let photos = tmpdir.path().join("photos");
+        fs::create_dir(&photos).unwrap();
+        let config = test_config(&photos, "");
+        let dedup_set = Arc::new(Mutex::new(HashSet::new()));
+        let magick = Arc::new(FakeMagick::new());
+
+        // Two sources with 8 photos each, 2 of them on both
+        let sources: Vec<PathBuf> = ["usb", "inbox"]
+            .iter()
+            .enumerate()
+            .map(|(s, name)| {
+                let dir = tmpdir.path().join(name);
+                fs::create_dir(&dir).unwrap();
+                for i in 0..8 {
+                    let content = if i < 2 {
+                        format!("shared photo {}", i)
+                    } else {
+                        format!("photo {} from source {}", i, s)
+                    };
+                    fs::write(dir.join(format!("IMG_{}_{}.jpg", s, i)), content).unwrap();
+                }
+                dir
+            })
+            .collect();
+
+        let handles: Vec<_> = sources
+            .into_iter()
+            .map(|dir| {
+                let photos = photos.clone();
+                let dedup_set = dedup_set.clone();
+                let config = config.clone();
+                let magick = magick.clone();
+                std::thread::spawn(move || {
+                    let importer = test_importer(&photos, &dedup_set, &config, &magick);
+                    import_directory(&dir, &importer).unwrap();
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let (index_path, meta) = index::find_index_file(&photos).unwrap();
+        let lines = fs::read_to_string(&index_path).unwrap().lines().count();
+        assert_eq!(meta.valid_count, 14);
+        assert_eq!(lines, 14);
+        assert_eq!(dedup_set.lock().unwrap().len(), 14);
+    }
+
+    #[test]
+    fn test_import_recovers_poisoned_lock() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let photos = tmpdir.path().join("photos");
+        fs::create_dir(&photos).unwrap();
+        let src = tmpdir.path().join("IMG_1.jpg");
+        fs::write(&src, b"photo").unwrap();
+        let config = test_config(&photos, "");
+        let dedup_set = Arc::new(Mutex::new(HashSet::new()));
+
+        let poisoner = dedup_set.clone();
+        std::thread::spawn(move || {
+            let _set = poisoner.lock().unwrap();
+            panic!("import thread crashed");
+        })
+        .join()
+        .unwrap_err();
+        assert!(dedup_set.is_poisoned());
+
+        let magick = FakeMagick::new();
+        let importer = test_importer(&photos, &dedup_set, &config, &magick);
+        let outcome = import_single_photo(&src, &importer).unwrap();
+        assert_eq!(outcome, ImportOutcome::Imported);
+    }
 
     #[test]
     fn test_compute_file_hash() {
@@ -379,6 +1000,215 @@ mod tests {
         assert_ne!(hash1, hash3);
     }
 
+    #[test]
+    fn test_import_rejects_oversized_file() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let src = tmpdir.path().join("big.jpg");
+        fs::write(&src, vec![0u8; 2048]).unwrap();
+
+        let config = test_config(tmpdir.path(), "max_import_size = 1024");
+        let dedup_set = Arc::new(Mutex::new(HashSet::new()));
+
+        let magick = FakeMagick::new();
+        let importer = test_importer(tmpdir.path(), &dedup_set, &config, &magick);
+        let result = import_single_photo(&src, &importer);
+        assert!(result.is_err());
+        assert!(dedup_set.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_scan_inbox() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let inbox = tmpdir.path().join("inbox");
+        let photos = tmpdir.path().join("photos");
+        fs::create_dir_all(inbox.join("phone")).unwrap();
+        fs::create_dir(&photos).unwrap();
+
+        let duplicate = inbox.join("phone/dup.jpg");
+        let too_big = inbox.join("big.jpg");
+        let copying = inbox.join("new.jpg");
+        fs::write(&duplicate, b"already in the library").unwrap();
+        fs::write(&too_big, vec![0u8; 2048]).unwrap();
+        fs::write(&copying, b"still arriving").unwrap();
+
+        let now = SystemTime::now();
+        let old = now - Duration::from_secs(60);
+        for path in [&duplicate, &too_big] {
+            File::options()
+                .write(true)
+                .open(path)
+                .unwrap()
+                .set_modified(old)
+                .unwrap();
+        }
+
+        let config = test_config(&photos, "max_import_size = 1024");
+        let dedup_set = Arc::new(Mutex::new(HashSet::from([
+            compute_file_hash(&duplicate).unwrap()
+        ])));
+        let mut failed = HashMap::new();
+        let magick = FakeMagick::new();
+        let importer = test_importer(&photos, &dedup_set, &config, &magick);
+
+        let imported = scan_inbox(&inbox, &importer, &mut failed, now);
+        assert_eq!(imported, 0);
+        // Duplicates are removed, failures and unsettled files are left alone
+        assert!(!duplicate.exists());
+        assert!(too_big.exists());
+        assert!(copying.exists());
+        assert_eq!(failed.get(&too_big), Some(&old));
+
+        // A failed file is not retried until it changes
+        fs::remove_file(&too_big).unwrap();
+        scan_inbox(&inbox, &importer, &mut failed, now);
+        assert!(failed.is_empty());
+    }
+
+    #[test]
+    fn test_rejected_photo_is_not_reconverted() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let source = tmpdir.path().join("usb");
+        let photos = tmpdir.path().join("photos");
+        fs::create_dir(&source).unwrap();
+        fs::create_dir(&photos).unwrap();
+        let dark = source.join("dark.jpg");
+        fs::write(&dark, b"dark lens cap shot").unwrap();
+        let config = test_config(&photos, "skip_bad_exposure = true");
+        let dedup_set = Arc::new(Mutex::new(HashSet::new()));
+
+        let magick = FakeMagick::new();
+        let importer = test_importer(&photos, &dedup_set, &config, &magick);
+        let import = || import_single_photo(&dark, &importer).unwrap();
+        assert_eq!(import(), ImportOutcome::Rejected);
+        assert_eq!(import(), ImportOutcome::Duplicate);
+
+        let conversions = fs::read_to_string(source.join("magick.log")).unwrap();
+        assert_eq!(conversions.lines().count(), 1);
+        assert!(find_images(&photos).is_empty());
+        let (_index_path, meta) = index::init_index(&photos).unwrap();
+        assert_eq!(meta.valid_count, 0);
+    }
+
+    #[test]
+    fn test_classify_exposure() {
+        assert_eq!(parse_exposure("0.0123 0.004\n"), Some((0.0123, 0.004)));
+        assert_eq!(parse_exposure("garbage"), None);
+
+        assert_eq!(
+            classify_exposure(0.02, 0.01),
+            Some(ExposureProblem::TooDark)
+        );
+        assert_eq!(
+            classify_exposure(0.98, 0.01),
+            Some(ExposureProblem::BlownOut)
+        );
+        assert_eq!(
+            classify_exposure(0.5, 0.01),
+            Some(ExposureProblem::LowContrast)
+        );
+        // A dark but detailed night shot is kept
+        assert_eq!(classify_exposure(0.12, 0.15), None);
+        assert_eq!(classify_exposure(0.45, 0.25), None);
+    }
+
+    #[test]
+    fn test_reserve_free_space_rotates_one_batch() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let (_index_path, meta) = index::init_index(tmpdir.path()).unwrap();
+        let mut writer = IndexWriter::open(tmpdir.path(), meta).unwrap();
+        let mut photos = Vec::new();
+        for i in 0..7 {
+            let photo = tmpdir.path().join(format!("{}.jpg", i));
+            fs::write(&photo, b"jpeg").unwrap();
+            writer
+                .append(&photo.to_string_lossy(), "orig.jpg", i)
+                .unwrap();
+            writer.sync_metadata().unwrap();
+            photos.push(photo);
+        }
+        let config = |min_free_space: u64| {
+            test_config(
+                tmpdir.path(),
+                &format!(
+                    "batch_delete_size = 2\nmin_photos_kept = 1\nmin_free_space = {}",
+                    min_free_space
+                ),
+            )
+        };
+        // Each rotated-out photo frees 100 bytes
+        let photos_ref = &photos;
+        let fake_free = |_: &Path| -> io::Result<u64> {
+            Ok(100 * photos_ref.iter().filter(|p| !p.exists()).count() as u64)
+        };
+        let remaining = || photos.iter().filter(|p| p.exists()).count();
+        let stalled = AtomicBool::new(false);
+        let reserve = |min_free_space| {
+            reserve_free_space(
+                tmpdir.path(),
+                tmpdir.path(),
+                &config(min_free_space),
+                &stalled,
+                fake_free,
+            )
+        };
+
+        // One batch is enough
+        reserve(150).unwrap();
+        assert_eq!(remaining(), 5);
+        assert!(!photos[0].exists() && !photos[1].exists());
+
+        // One batch is not enough: skip the import and stop rotating
+        let err = reserve(10_000).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::StorageFull);
+        assert_eq!(remaining(), 3);
+        assert!(stalled.load(Ordering::Relaxed));
+        reserve(10_000).unwrap_err();
+        assert_eq!(remaining(), 3);
+
+        // Free space recovered, so rotation may run again
+        reserve(150).unwrap();
+        assert!(!stalled.load(Ordering::Relaxed));
+
+        // min_photos_kept caps the batch, then nothing is left to rotate
+        reserve(10_000).unwrap_err();
+        assert_eq!(remaining(), 1);
+        stalled.store(false, Ordering::Relaxed);
+        reserve(10_000).unwrap_err();
+        assert_eq!(remaining(), 1);
+        assert!(stalled.load(Ordering::Relaxed));
+        let (_index_path, meta) = index::init_index(tmpdir.path()).unwrap();
+        assert_eq!(meta.valid_count, 1);
+    }
+
+    #[test]
+    fn test_reserve_free_space_stops_when_nothing_freed() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let (_index_path, meta) = index::init_index(tmpdir.path()).unwrap();
+        let mut writer = IndexWriter::open(tmpdir.path(), meta).unwrap();
+        for i in 0..5 {
+            let photo = tmpdir.path().join(format!("{}.jpg", i));
+            fs::write(&photo, b"jpeg").unwrap();
+            writer
+                .append(&photo.to_string_lossy(), "orig.jpg", i)
+                .unwrap();
+            writer.sync_metadata().unwrap();
+        }
+        let config = test_config(
+            tmpdir.path(),
+            "batch_delete_size = 2\nmin_photos_kept = 0\nmin_free_space = 1000",
+        );
+        // Something else keeps the volume full
+        let stalled = AtomicBool::new(false);
+        for _ in 0..3 {
+            let err =
+                reserve_free_space(tmpdir.path(), tmpdir.path(), &config, &stalled, |_| Ok(0))
+                    .unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::StorageFull);
+        }
+        let (_index_path, meta) = index::init_index(tmpdir.path()).unwrap();
+        assert_eq!(meta.valid_count, 3);
+    }
+
     #[test]
     fn test_find_images() {
         let tmpdir = tempfile::tempdir().unwrap();
@@ -386,6 +1216,8 @@ mod tests {
         File::create(tmpdir.path().join("photo2.JPEG")).unwrap();
         File::create(tmpdir.path().join("photo3.heif")).unwrap();
         File::create(tmpdir.path().join("photo4.HEIC")).unwrap();
+        File::create(tmpdir.path().join("poster.pdf")).unwrap();
+        File::create(tmpdir.path().join("logo.svg")).unwrap();
         File::create(tmpdir.path().join("notaphoto.txt")).unwrap();
 
         let subdir = tmpdir.path().join("subdir");
@@ -393,7 +1225,32 @@ mod tests {
         File::create(subdir.join("nested.jpg")).unwrap();
 
         let images = find_images(tmpdir.path());
-        assert_eq!(images.len(), 5);
+        assert_eq!(images.len(), 7);
+
+        // A directory that vanished before the scan yields nothing
+        assert!(find_images(&tmpdir.path().join("gone")).is_empty());
+    }
+
+    #[test]
+    fn test_find_images_skips_unreadable_dir() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let locked = tmpdir.path().join("locked");
+        let other = tmpdir.path().join("other");
+        fs::create_dir(&locked).unwrap();
+        fs::create_dir(&other).unwrap();
+        File::create(locked.join("hidden.jpg")).unwrap();
+        File::create(tmpdir.path().join("top.jpg")).unwrap();
+        File::create(other.join("nested.jpg")).unwrap();
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+
+        let images = find_images(tmpdir.path());
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+        assert!(images.contains(&tmpdir.path().join("top.jpg")));
+        assert!(images.contains(&other.join("nested.jpg")));
+        // Root can read the locked directory anyway
+        if unsafe { libc::geteuid() } != 0 {
+            assert_eq!(images.len(), 2);
+        }
     }
 
     #[test]
@@ -406,4 +1263,33 @@ mod tests {
         assert!(dest_str.contains("/photos/2021/01/01/"));
         assert!(dest_str.contains("myphoto.jpg"));
     }
+
+    #[test]
+    fn test_build_dest_path_non_jpeg_gets_jpg_extension() {
+        let photos_dir = PathBuf::from("/photos");
+        let mtime = UNIX_EPOCH + Duration::from_secs(1609459200);
+
+        let dest = build_dest_path(Path::new("/usb/menu.PDF"), &photos_dir, mtime);
+        assert!(dest.to_string_lossy().ends_with("_menu.jpg"));
+
+        let dest = build_dest_path(Path::new("/usb/logo.svg"), &photos_dir, mtime);
+        assert!(dest.to_string_lossy().ends_with("_logo.jpg"));
+
+        let dest = build_dest_path(Path::new("/usb/beach.JPEG"), &photos_dir, mtime);
+        assert!(dest.to_string_lossy().ends_with("_beach.JPEG"));
+    }
+
+    #[test]
+    fn test_magick_input_arg() {
+        assert_eq!(
+            magick_input_arg(Path::new("/usb/menu.pdf")),
+            std::ffi::OsString::from("/usb/menu.pdf[0]")
+        );
+        assert_eq!(
+            magick_input_arg(Path::new("/usb/logo.svg")),
+            std::ffi::OsString::from("/usb/logo.svg")
+        );
+        assert!(is_vector_image(Path::new("/usb/logo.SVG")));
+        assert!(!is_vector_image(Path::new("/usb/photo.jpg")));
+    }
 }
@@ -139,7 +139,8 @@ impl Log for TmpfsLogger {
     fn log(&self, record: &Record) {
         if self.enabled(record.metadata()) {
             if let Err(e) = self.write_log(record) {
-                eprintln!("Logger error: {}", e);
+                // Keep the line itself, so it still reaches the journal
+                eprintln!("Logger error: {} ({} {})", e, record.level(), record.args());
             }
         }
     }
@@ -16,10 +16,14 @@
 
 mod app;
 mod config;
+mod crash;
 mod display;
+mod hooks;
 mod import;
 mod index;
 mod logger;
+mod monitor;
+mod takeout;
 
 use config::Config;
 use std::fs::OpenOptions;
@@ -61,7 +65,7 @@ fn acquire_pid_lock() -> Result<std::fs::File, String> {
         .write(true)
         .truncate(true)
         .open(lock_path)
-        .map_err(|e| format!("Failed to open lock file: {}", e))?;
+        .map_err(|e| format!("Failed to open lock file {}: {}", lock_path.display(), e))?;
 
     let pid = std::process::id();
     writeln!(file, "{}", pid).map_err(|e| format!("Failed to write PID: {}", e))?;
@@ -89,6 +93,7 @@ fn print_help(name: &str) {
     println!();
     println!("Options:");
     println!("  --import-dir <dir>   Import photos from a local directory and exit");
+    println!("  --seed <n>           Start the slideshow from the same photo every run");
     println!("  -h, --help           Print this help message and exit");
 }
 
@@ -98,6 +103,7 @@ fn main() {
     // Parse optional flags
     let mut import_dir: Option<PathBuf> = None;
     let mut config_path_arg: Option<String> = None;
+    let mut seed: Option<u64> = None;
 
     let mut i = 1;
     while i < args.len() {
@@ -112,6 +118,16 @@ fn main() {
             }
             import_dir = Some(PathBuf::from(&args[i + 1]));
             i += 2;
+        } else if args[i] == "--seed" {
+            match args.get(i + 1).map(|s| s.parse::<u64>()) {
+                Some(Ok(n)) => seed = Some(n),
+                _ => {
+                    eprintln!("Error: --seed requires a non-negative integer");
+                    eprintln!("Usage: {} [OPTIONS] <config.toml>", args[0]);
+                    std::process::exit(1);
+                }
+            }
+            i += 2;
         } else if args[i].starts_with("-") {
             eprintln!("Error: unknown option {}", args[i]);
             eprintln!("Usage: {} [OPTIONS] <config.toml>", args[0]);
@@ -146,9 +162,47 @@ fn main() {
         }
     };
 
+    // Report every location we write to that isn't writable, e.g. on a
+    // read-only root. Logs fall back to the runtime directory (tmpfs).
+    let mut write_problems = Vec::new();
+    let log_dir = match config::check_writable(&config.log_dir) {
+        Ok(()) => config.log_dir.clone(),
+        Err(e) => {
+            let fallback = config
+                .socket_path
+                .parent()
+                .unwrap_or(std::path::Path::new("/run"))
+                .to_path_buf();
+            write_problems.push(format!(
+                "log_dir {} is not writable ({}), logging to {} instead",
+                config.log_dir.display(),
+                e,
+                fallback.display()
+            ));
+            if let Err(e) = config::check_writable(&fallback) {
+                write_problems.push(format!(
+                    "log fallback {} is not writable either ({}), log lines will only go to stderr",
+                    fallback.display(),
+                    e
+                ));
+            }
+            fallback
+        }
+    };
+    if let Err(e) = config::check_writable(&config.photos_dir) {
+        write_problems.push(format!(
+            "photos_dir {} is not writable ({}), imports and index compaction will fail",
+            config.photos_dir.display(),
+            e
+        ));
+    }
+    for problem in &write_problems {
+        eprintln!("{}", problem);
+    }
+
     // Initialize logger
     if let Err(e) = logger::TmpfsLogger::init(
-        PathBuf::from("/tmp/photo-frame.log"),
+        log_dir.join("photo-frame.log"),
         config.log_max_size,
         config.log_max_files,
     ) {
@@ -156,8 +210,15 @@ fn main() {
         std::process::exit(1);
     }
 
-    log::info!("Starting photo-frame-manager");
+    match &config.frame_name {
+        Some(name) => log::info!("Starting photo-frame-manager for frame \"{}\"", name),
+        None => log::info!("Starting photo-frame-manager"),
+    }
     log::info!("{}", config);
+    for problem in &write_problems {
+        log::warn!("{}", problem);
+    }
+    crash::install_panic_hook(config.to_string(), log_dir.join(crash::CRASH_FILE_NAME));
 
     // Ensure photos directory exists
     if let Err(e) = std::fs::create_dir_all(&config.photos_dir) {
@@ -257,36 +318,61 @@ fn main() {
         }
     };
 
-    // Spawn display thread
-    let display_shutdown = shutdown.clone();
+    // Spawn display thread, restarted if it panics or fails
     let display_socket = config.socket_path.clone();
     let display_photos_dir = config.photos_dir.clone();
-    let _display_handle = std::thread::spawn(move || {
-        if let Err(e) =
-            app::run_display_loop(&display_photos_dir, &display_socket, display_shutdown)
-        {
-            log::error!("Display loop error: {}", e);
-        }
+    let display_show_new = config.show_new_photos_immediately;
+    let display_shutdown = shutdown.clone();
+    let _display_handle = crash::supervise("display", shutdown.clone(), move || {
+        app::run_display_loop(
+            &display_photos_dir,
+            &display_socket,
+            seed,
+            display_show_new,
+            display_shutdown.clone(),
+        )
     });
 
-    // Spawn USB watcher thread
-    let usb_photos_dir = config.photos_dir.clone();
-    let usb_index_dir = config.photos_dir.clone();
+    // Spawn USB watcher thread, restarted if it panics or fails
     let usb_dedup_set = dedup_set.clone();
     let usb_config = config.clone();
     let usb_shutdown = shutdown.clone();
-    let _usb_handle = std::thread::spawn(move || {
-        if let Err(e) = import::watch_usb_mounts(
-            usb_photos_dir,
-            usb_index_dir,
-            usb_dedup_set,
-            usb_config,
-            usb_shutdown,
-        ) {
-            log::error!("USB watcher error: {}", e);
-        }
+    let _usb_handle = crash::supervise("usb-watcher", shutdown.clone(), move || {
+        import::watch_usb_mounts(
+            usb_config.photos_dir.clone(),
+            usb_config.photos_dir.clone(),
+            usb_dedup_set.clone(),
+            usb_config.clone(),
+            usb_shutdown.clone(),
+        )
     });
 
+    // Spawn inbox watcher thread, restarted if it panics or fails
+    if let Some(inbox_dir) = config.inbox_dir.clone() {
+        let inbox_dedup_set = dedup_set.clone();
+        let inbox_config = config.clone();
+        let inbox_shutdown = shutdown.clone();
+        let _inbox_handle = crash::supervise("inbox-watcher", shutdown.clone(), move || {
+            import::watch_inbox(
+                inbox_dir.clone(),
+                inbox_config.photos_dir.clone(),
+                inbox_config.photos_dir.clone(),
+                inbox_dedup_set.clone(),
+                inbox_config.clone(),
+                inbox_shutdown.clone(),
+            )
+        });
+    }
+
+    // Spawn system monitor thread
+    if config.monitor_interval_secs > 0 {
+        let monitor_config = config.clone();
+        let monitor_shutdown = shutdown.clone();
+        let _monitor_handle = std::thread::spawn(move || {
+            monitor::run_monitor(monitor_config, monitor_shutdown);
+        });
+    }
+
     // Wait for signal
     for sig in signals.forever() {
         match sig {
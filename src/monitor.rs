@@ -0,0 +1,587 @@
+// Photo Frame Manager — DRM/GBM/EGL digital photo frame.
+// Copyright (C) 2026 Daniel Mikusa <dan@mikusa.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::config::Config;
+use crate::hooks::{self, HookEvent};
+use crate::import;
+use std::collections::VecDeque;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const THERMAL_ZONE: &str = "/sys/class/thermal/thermal_zone0/temp";
+const LOADAVG: &str = "/proc/loadavg";
+const PROC_SELF_STATUS: &str = "/proc/self/status";
+
+/// How much RSS history the memory growth trend is fitted over.
+const MEMORY_TREND_WINDOW: Duration = Duration::from_secs(6 * 3600);
+
+/// History needed before a trend is reported, so the allocations made while
+/// starting up (dedup set, index reader) are not mistaken for a leak.
+const MEMORY_TREND_MIN_SPAN: Duration = Duration::from_secs(3600);
+
+/// Degrees below `cpu_temp_warning` before a temperature warning is cleared,
+/// so a SoC hovering at the limit doesn't log on every sample.
+const TEMP_HYSTERESIS: f32 = 5.0;
+
+/// Current-state bits from `vcgencmd get_throttled`. The same bits shifted
+/// left by 16 record whether each condition has occurred since boot.
+const THROTTLE_FLAGS: &[(u32, &str)] = &[
+    (1 << 0, "under-voltage"),
+    (1 << 1, "arm frequency capped"),
+    (1 << 2, "throttled"),
+    (1 << 3, "soft temperature limit"),
+];
+
+/// One sample of system health. Each reading is `None` when the source is
+/// unavailable (e.g. no `vcgencmd` off the Pi).
+#[derive(Debug, Clone, PartialEq)]
+pub struct SystemStats {
+    pub cpu_temp: Option<f32>,
+    pub load_1m: Option<f32>,
+    pub throttled: Option<u32>,
+    /// Bytes free on the photos volume.
+    pub free_space: Option<u64>,
+    /// Resident set size of the manager, in bytes.
+    pub rss: Option<u64>,
+    pub taken_at: Instant,
+}
+
+/// Source of the health readings. The monitor reads sysfs, `/proc`, and
+/// `vcgencmd`; tests substitute scripted readings. Each returns `None` if the
+/// value cannot be read.
+pub trait SystemProbe {
+    /// CPU temperature in degrees Celsius.
+    fn cpu_temp(&self) -> Option<f32>;
+    /// 1-minute load average.
+    fn load_1m(&self) -> Option<f32>;
+    /// Raw `vcgencmd get_throttled` flags.
+    fn throttled(&self) -> Option<u32>;
+    /// Bytes free on the filesystem holding `path`.
+    fn free_space(&self, path: &Path) -> Option<u64>;
+    /// Resident set size of the manager in bytes.
+    fn rss(&self) -> Option<u64>;
+}
+
+/// Reads the real system.
+pub struct HostProbe;
+
+impl SystemProbe for HostProbe {
+    fn cpu_temp(&self) -> Option<f32> {
+        read_to_string(THERMAL_ZONE).and_then(|s| parse_millidegrees(&s))
+    }
+
+    fn load_1m(&self) -> Option<f32> {
+        read_to_string(LOADAVG).and_then(|s| parse_loadavg(&s))
+    }
+
+    fn throttled(&self) -> Option<u32> {
+        read_throttled()
+    }
+
+    fn free_space(&self, path: &Path) -> Option<u64> {
+        import::free_space(path).ok()
+    }
+
+    fn rss(&self) -> Option<u64> {
+        read_to_string(PROC_SELF_STATUS).and_then(|s| parse_vm_rss(&s))
+    }
+}
+
+impl SystemStats {
+    pub fn sample(photos_dir: &Path, probe: &dyn SystemProbe) -> Self {
+        SystemStats {
+            cpu_temp: probe.cpu_temp(),
+            load_1m: probe.load_1m(),
+            throttled: probe.throttled(),
+            free_space: probe.free_space(photos_dir),
+            rss: probe.rss(),
+            taken_at: Instant::now(),
+        }
+    }
+}
+
+fn read_to_string(path: &str) -> Option<String> {
+    std::fs::read_to_string(Path::new(path)).ok()
+}
+
+fn read_throttled() -> Option<u32> {
+    let output = Command::new("vcgencmd")
+        .arg("get_throttled")
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_throttled(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Parse a sysfs thermal zone reading ("48312") into degrees Celsius.
+fn parse_millidegrees(s: &str) -> Option<f32> {
+    s.trim().parse::<i64>().ok().map(|m| m as f32 / 1000.0)
+}
+
+/// Parse the 1-minute load average from `/proc/loadavg`.
+fn parse_loadavg(s: &str) -> Option<f32> {
+    s.split_whitespace().next()?.parse().ok()
+}
+
+/// Parse `vcgencmd get_throttled` output ("throttled=0x50005").
+fn parse_throttled(s: &str) -> Option<u32> {
+    let hex = s.trim().strip_prefix("throttled=")?;
+    u32::from_str_radix(hex.trim_start_matches("0x"), 16).ok()
+}
+
+/// Parse the `VmRSS:  12345 kB` line of `/proc/<pid>/status` into bytes.
+fn parse_vm_rss(status: &str) -> Option<u64> {
+    let line = status.lines().find(|l| l.starts_with("VmRSS:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+/// Names of the throttling conditions active right now.
+fn throttle_reasons(flags: u32) -> Vec<&'static str> {
+    THROTTLE_FLAGS
+        .iter()
+        .filter(|(bit, _)| flags & bit != 0)
+        .map(|(_, name)| *name)
+        .collect()
+}
+
+/// RSS samples over the last `MEMORY_TREND_WINDOW`, used to estimate how
+/// fast memory use is growing.
+#[derive(Debug, Default)]
+struct MemoryTrend {
+    samples: VecDeque<(Instant, u64)>,
+}
+
+impl MemoryTrend {
+    fn add(&mut self, at: Instant, rss: u64) {
+        self.samples.push_back((at, rss));
+        while let Some(&(first, _)) = self.samples.front() {
+            if at.duration_since(first) <= MEMORY_TREND_WINDOW {
+                break;
+            }
+            self.samples.pop_front();
+        }
+    }
+
+    /// Growth rate in MiB per hour from a least-squares fit over the window,
+    /// or `None` until there is at least `MEMORY_TREND_MIN_SPAN` of history.
+    fn growth_mib_per_hour(&self) -> Option<f64> {
+        let (first, _) = *self.samples.front()?;
+        let (last, _) = *self.samples.back()?;
+        if last.duration_since(first) < MEMORY_TREND_MIN_SPAN {
+            return None;
+        }
+
+        let n = self.samples.len() as f64;
+        let points: Vec<(f64, f64)> = self
+            .samples
+            .iter()
+            .map(|&(t, rss)| (t.duration_since(first).as_secs_f64(), rss as f64))
+            .collect();
+        let mean_t = points.iter().map(|p| p.0).sum::<f64>() / n;
+        let mean_rss = points.iter().map(|p| p.1).sum::<f64>() / n;
+        let covariance: f64 = points
+            .iter()
+            .map(|(t, rss)| (t - mean_t) * (rss - mean_rss))
+            .sum();
+        let variance: f64 = points.iter().map(|(t, _)| (t - mean_t).powi(2)).sum();
+
+        let bytes_per_sec = covariance / variance;
+        Some(bytes_per_sec * 3600.0 / (1024.0 * 1024.0))
+    }
+}
+
+/// What has already been reported, so each condition is logged when it
+/// starts and when it clears rather than on every sample.
+#[derive(Debug, Default)]
+struct MonitorState {
+    hot: bool,
+    throttled: u32,
+    low_disk: bool,
+    memory: MemoryTrend,
+    leaking: bool,
+    last_memory_log: Option<Instant>,
+}
+
+impl MonitorState {
+    fn update(&mut self, stats: &SystemStats, config: &Config) {
+        let cpu_temp_warning = config.cpu_temp_warning;
+        if let Some(temp) = stats.cpu_temp {
+            if !self.hot && temp >= cpu_temp_warning {
+                self.hot = true;
+                log::warn!(
+                    "CPU temperature {:.1}°C is above {:.1}°C (load {})",
+                    temp,
+                    cpu_temp_warning,
+                    format_load(stats.load_1m)
+                );
+            } else if self.hot && temp < cpu_temp_warning - TEMP_HYSTERESIS {
+                self.hot = false;
+                log::info!("CPU temperature back to {:.1}°C", temp);
+            }
+        }
+
+        if let Some(flags) = stats.throttled {
+            let current = flags & 0xf;
+            if current != self.throttled {
+                if current != 0 {
+                    log::warn!(
+                        "SoC throttling active: {} (get_throttled=0x{:x})",
+                        throttle_reasons(current).join(", "),
+                        flags
+                    );
+                } else {
+                    log::info!("SoC throttling cleared (get_throttled=0x{:x})", flags);
+                }
+                self.throttled = current;
+            }
+        }
+
+        // Imports rotate old photos out when they find the volume low; this
+        // catches space taken by something else between imports.
+        if let Some(free) = stats.free_space.filter(|_| config.min_free_space > 0) {
+            let low = free < config.min_free_space;
+            if low && !self.low_disk {
+                log::warn!(
+                    "Only {} bytes free on {}, below min_free_space ({})",
+                    free,
+                    config.photos_dir.display(),
+                    config.min_free_space
+                );
+            } else if !low && self.low_disk {
+                log::info!("Free space back to {} bytes", free);
+            }
+            self.low_disk = low;
+        }
+
+        if let Some(rss) = stats.rss {
+            self.memory.add(stats.taken_at, rss);
+            self.check_memory_growth(rss, config);
+            self.log_memory_periodically(stats.taken_at, rss, config);
+        }
+    }
+
+    /// Log RSS and the current trend every `memory_log_interval_secs`, so
+    /// slow growth below the warning threshold is still visible in the log.
+    fn log_memory_periodically(&mut self, at: Instant, rss: u64, config: &Config) {
+        if config.memory_log_interval_secs == 0 {
+            return;
+        }
+        let interval = Duration::from_secs(config.memory_log_interval_secs);
+        if self
+            .last_memory_log
+            .is_some_and(|last| at.duration_since(last) < interval)
+        {
+            return;
+        }
+        self.last_memory_log = Some(at);
+        log::info!(
+            "Memory: RSS {} KiB, trend {}",
+            rss / 1024,
+            self.memory.growth_mib_per_hour().map_or_else(
+                || "not yet known".to_string(),
+                |g| format!("{:.2} MiB/hour", g)
+            )
+        );
+    }
+
+    fn check_memory_growth(&mut self, rss: u64, config: &Config) {
+        let threshold = config.memory_growth_warning as f64;
+        if threshold <= 0.0 {
+            return;
+        }
+        let Some(growth) = self.memory.growth_mib_per_hour() else {
+            return;
+        };
+
+        if !self.leaking && growth >= threshold {
+            self.leaking = true;
+            log::warn!(
+                "Memory use growing at {:.1} MiB/hour (RSS {} KiB), above memory_growth_warning ({:.1})",
+                growth,
+                rss / 1024,
+                threshold
+            );
+            hooks::run_hook(
+                config,
+                HookEvent::MemoryGrowth,
+                &[
+                    ("PHOTO_FRAME_RSS", rss.to_string()),
+                    ("PHOTO_FRAME_GROWTH_MIB_PER_HOUR", format!("{:.1}", growth)),
+                ],
+            );
+        } else if self.leaking && growth < threshold / 2.0 {
+            self.leaking = false;
+            log::info!(
+                "Memory growth back to {:.1} MiB/hour (RSS {} KiB)",
+                growth,
+                rss / 1024
+            );
+        }
+    }
+}
+
+fn format_load(load: Option<f32>) -> String {
+    load.map_or_else(|| "unknown".to_string(), |l| format!("{:.2}", l))
+}
+
+/// Sample system health every `monitor_interval_secs` until shutdown,
+/// logging when the SoC runs hot or is throttled.
+pub fn run_monitor(config: Config, shutdown: Arc<AtomicBool>) {
+    let interval = Duration::from_secs(config.monitor_interval_secs);
+    let mut state = MonitorState::default();
+
+    let probe = HostProbe;
+    let mut stats = SystemStats::sample(&config.photos_dir, &probe);
+    log::info!(
+        "System monitor started (every {}s): cpu_temp={} load={} throttled={} free_space={} rss={}",
+        config.monitor_interval_secs,
+        stats
+            .cpu_temp
+            .map_or_else(|| "unknown".to_string(), |t| format!("{:.1}°C", t)),
+        format_load(stats.load_1m),
+        stats
+            .throttled
+            .map_or_else(|| "unknown".to_string(), |f| format!("0x{:x}", f)),
+        stats
+            .free_space
+            .map_or_else(|| "unknown".to_string(), |b| b.to_string()),
+        stats
+            .rss
+            .map_or_else(|| "unknown".to_string(), |b| b.to_string())
+    );
+
+    loop {
+        state.update(&stats, &config);
+
+        let next = Instant::now() + interval;
+        while Instant::now() < next {
+            if shutdown.load(Ordering::Relaxed) {
+                return;
+            }
+            std::thread::sleep(Duration::from_secs(1));
+        }
+        stats = SystemStats::sample(&config.photos_dir, &probe);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    /// Fixed temperature, throttling, and free space, with RSS that grows
+    /// by a fixed step on every reading.
+    struct MockProbe {
+        cpu_temp: f32,
+        throttled: u32,
+        free_space: u64,
+        rss: Cell<u64>,
+        step: u64,
+    }
+
+    impl MockProbe {
+        fn new(rss: u64, step: u64) -> Self {
+            MockProbe {
+                cpu_temp: 50.0,
+                throttled: 0,
+                free_space: 1 << 30,
+                rss: Cell::new(rss),
+                step,
+            }
+        }
+    }
+
+    impl SystemProbe for MockProbe {
+        fn cpu_temp(&self) -> Option<f32> {
+            Some(self.cpu_temp)
+        }
+
+        fn load_1m(&self) -> Option<f32> {
+            Some(0.5)
+        }
+
+        fn throttled(&self) -> Option<u32> {
+            Some(self.throttled)
+        }
+
+        fn free_space(&self, _path: &Path) -> Option<u64> {
+            Some(self.free_space)
+        }
+
+        fn rss(&self) -> Option<u64> {
+            let rss = self.rss.get();
+            self.rss.set(rss + self.step);
+            Some(rss)
+        }
+    }
+
+    #[test]
+    fn test_parse_readings() {
+        assert_eq!(parse_millidegrees("48312\n"), Some(48.312));
+        assert_eq!(parse_millidegrees("garbage"), None);
+        assert_eq!(parse_loadavg("0.52 0.58 0.59 1/123 4567\n"), Some(0.52));
+        assert_eq!(parse_loadavg(""), None);
+        assert_eq!(parse_throttled("throttled=0x50005\n"), Some(0x50005));
+        assert_eq!(parse_throttled("throttled=0x0"), Some(0));
+        assert_eq!(parse_throttled("error"), None);
+        assert_eq!(
+            parse_vm_rss("Name:\tphoto-frame\nVmRSS:\t    5120 kB\nThreads:\t3\n"),
+            Some(5120 * 1024)
+        );
+        assert_eq!(parse_vm_rss("Name:\tkthreadd\n"), None);
+        assert_eq!(
+            throttle_reasons(0x50005),
+            vec!["under-voltage", "throttled"]
+        );
+    }
+
+    fn test_config() -> Config {
+        toml::from_str(
+            r#"
+photos_dir = "/tmp"
+socket_path = "/tmp/sock"
+native_resolution = "1920x1080"
+cpu_temp_warning = 75.0
+min_free_space = 1000
+"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_monitor_state_transitions() {
+        let config = test_config();
+        let mut state = MonitorState::default();
+        let mut stats = SystemStats {
+            cpu_temp: Some(80.0),
+            load_1m: Some(1.0),
+            throttled: Some(0x50005),
+            free_space: Some(500),
+            rss: None,
+            taken_at: Instant::now(),
+        };
+        state.update(&stats, &config);
+        assert!(state.hot);
+        assert_eq!(state.throttled, 0x5);
+        assert!(state.low_disk);
+
+        // Within the hysteresis band: still hot
+        stats.cpu_temp = Some(72.0);
+        stats.throttled = Some(0x50000);
+        stats.free_space = Some(5000);
+        state.update(&stats, &config);
+        assert!(state.hot);
+        assert_eq!(state.throttled, 0);
+        assert!(!state.low_disk);
+
+        stats.cpu_temp = Some(60.0);
+        state.update(&stats, &config);
+        assert!(!state.hot);
+    }
+
+    #[test]
+    fn test_memory_trend() {
+        let start = Instant::now();
+        let mut trend = MemoryTrend::default();
+        let mib = 1024 * 1024;
+
+        // 10 MiB/hour, sampled every 10 minutes
+        for i in 0..6 {
+            trend.add(
+                start + Duration::from_secs(i * 600),
+                50 * mib + i * mib * 10 / 6,
+            );
+        }
+        // Less than an hour of history
+        assert_eq!(trend.growth_mib_per_hour(), None);
+
+        trend.add(start + Duration::from_secs(3600), 60 * mib);
+        let growth = trend.growth_mib_per_hour().unwrap();
+        assert!((growth - 10.0).abs() < 0.1, "growth was {}", growth);
+
+        // Old samples fall out of the window
+        trend.add(start + Duration::from_secs(8 * 3600), 60 * mib);
+        assert!(trend.samples.len() < 8);
+    }
+
+    #[test]
+    fn test_monitor_flags_growth_from_probe() {
+        let config = test_config();
+        let mib = 1024 * 1024;
+        let start = Instant::now();
+
+        // Samples every 10 minutes for an hour; 1 MiB per sample is 6 MiB/hour,
+        // above the default memory_growth_warning of 4.0
+        for (step, expect_leak) in [(mib, true), (0, false)] {
+            let probe = MockProbe::new(50 * mib, step);
+            let mut state = MonitorState::default();
+            for i in 0..7 {
+                let mut stats = SystemStats::sample(Path::new("/tmp"), &probe);
+                stats.taken_at = start + Duration::from_secs(i * 600);
+                state.update(&stats, &config);
+            }
+            assert_eq!(state.leaking, expect_leak);
+            assert!(!state.hot && state.throttled == 0 && !state.low_disk);
+        }
+    }
+
+    #[test]
+    fn test_monitor_flags_conditions_from_probe() {
+        let config = test_config();
+        let probe = MockProbe {
+            cpu_temp: 81.5,
+            throttled: 0x50005,
+            free_space: 500,
+            ..MockProbe::new(0, 0)
+        };
+        let stats = SystemStats::sample(Path::new("/nonexistent"), &probe);
+        assert_eq!(stats.cpu_temp, Some(81.5));
+        assert_eq!(stats.free_space, Some(500));
+
+        let mut state = MonitorState::default();
+        state.update(&stats, &config);
+        assert!(state.hot);
+        assert_eq!(state.throttled, 0x5);
+        assert!(state.low_disk);
+    }
+
+    #[test]
+    fn test_memory_logged_at_interval() {
+        let mut config = test_config();
+        config.memory_log_interval_secs = 3600;
+        let start = Instant::now();
+        let mut state = MonitorState::default();
+
+        state.log_memory_periodically(start, 1024, &config);
+        assert_eq!(state.last_memory_log, Some(start));
+
+        let later = start + Duration::from_secs(1800);
+        state.log_memory_periodically(later, 1024, &config);
+        assert_eq!(state.last_memory_log, Some(start));
+
+        let hour = start + Duration::from_secs(3600);
+        state.log_memory_periodically(hour, 1024, &config);
+        assert_eq!(state.last_memory_log, Some(hour));
+    }
+}
@@ -0,0 +1,192 @@
+// Photo Frame Manager — DRM/GBM/EGL digital photo frame.
+// Copyright (C) 2026 Daniel Mikusa <dan@mikusa.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Suffix Google appends to copies edited in Google Photos. The edited copy
+/// shares the original's sidecar.
+const EDITED_SUFFIX: &str = "-edited";
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Sidecar {
+    photo_taken_time: Option<Timestamp>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Timestamp {
+    /// Seconds since the epoch, as a string.
+    timestamp: String,
+}
+
+/// Capture time from the Google Photos Takeout sidecar for `photo`, if there
+/// is one. Takeout sets every file's mtime to the export date and keeps the
+/// real capture time in a JSON file next to each photo.
+pub fn taken_time(photo: &Path) -> Option<SystemTime> {
+    let sidecar = find_sidecar(photo)?;
+    let contents = fs::read_to_string(&sidecar).ok()?;
+    let taken = parse_taken_time(&contents);
+    if taken.is_none() {
+        log::warn!("No photoTakenTime in {}", sidecar.display());
+    }
+    taken
+}
+
+/// Sidecar names used by Takeout: `IMG_1.jpg.json` in older exports and
+/// `IMG_1.jpg.supplemental-metadata.json` in newer ones.
+fn find_sidecar(photo: &Path) -> Option<PathBuf> {
+    let name = photo.file_name()?.to_string_lossy().to_string();
+    let mut names = vec![name.clone()];
+    if let Some(original) = unedited_name(&name) {
+        names.push(original);
+    }
+
+    names
+        .iter()
+        .flat_map(|n| sidecar_names(n))
+        .map(|sidecar| photo.with_file_name(sidecar))
+        .find(|path| path.is_file())
+}
+
+/// Candidate sidecar names for a photo named `name`. A renamed duplicate
+/// `IMG_1(1).jpg` usually has `IMG_1.jpg(1).json`, with the number moved
+/// after the extension, but some exports use `IMG_1(1).jpg.json`.
+fn sidecar_names(name: &str) -> Vec<String> {
+    let mut names = vec![
+        format!("{}.json", name),
+        format!("{}.supplemental-metadata.json", name),
+    ];
+    if let Some((base, n)) = split_duplicate_number(name) {
+        names.push(format!("{}({}).json", base, n));
+        names.push(format!("{}.supplemental-metadata({}).json", base, n));
+    }
+    names
+}
+
+/// `IMG_1(1).jpg` -> (`IMG_1.jpg`, `1`)
+fn split_duplicate_number(name: &str) -> Option<(String, &str)> {
+    let (stem, ext) = name.rsplit_once('.')?;
+    let (base, rest) = stem.rsplit_once('(')?;
+    let n = rest.strip_suffix(')')?;
+    if n.is_empty() || !n.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    Some((format!("{}.{}", base, ext), n))
+}
+
+/// `IMG_1-edited.jpg` -> `IMG_1.jpg`
+fn unedited_name(name: &str) -> Option<String> {
+    let (stem, ext) = name.rsplit_once('.')?;
+    let stem = stem.strip_suffix(EDITED_SUFFIX)?;
+    Some(format!("{}.{}", stem, ext))
+}
+
+fn parse_taken_time(json: &str) -> Option<SystemTime> {
+    let sidecar: Sidecar = serde_json::from_str(json).ok()?;
+    let secs: u64 = sidecar.photo_taken_time?.timestamp.parse().ok()?;
+    // Takeout uses 0 when the capture time is unknown.
+    if secs == 0 {
+        return None;
+    }
+    Some(UNIX_EPOCH + Duration::from_secs(secs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_taken_time() {
+        let json = r#"{
+  "title": "IMG_1234.jpg",
+  "description": "",
+  "photoTakenTime": { "timestamp": "1609459200", "formatted": "Jan 1, 2021, 12:00:00 AM UTC" },
+  "favorited": true
+}"#;
+        assert_eq!(
+            parse_taken_time(json),
+            Some(UNIX_EPOCH + Duration::from_secs(1_609_459_200))
+        );
+        assert_eq!(parse_taken_time(r#"{"title": "x.jpg"}"#), None);
+        assert_eq!(
+            parse_taken_time(r#"{"photoTakenTime": {"timestamp": "0"}}"#),
+            None
+        );
+        assert_eq!(parse_taken_time("not json"), None);
+    }
+
+    #[test]
+    fn test_taken_time_finds_sidecar() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let sidecar = r#"{"photoTakenTime": {"timestamp": "1609459200"}}"#;
+        std::fs::write(
+            tmpdir.path().join("IMG_1.jpg.supplemental-metadata.json"),
+            sidecar,
+        )
+        .unwrap();
+        std::fs::write(tmpdir.path().join("IMG_2.jpg.json"), sidecar).unwrap();
+
+        let expected = Some(UNIX_EPOCH + Duration::from_secs(1_609_459_200));
+        assert_eq!(taken_time(&tmpdir.path().join("IMG_1.jpg")), expected);
+        assert_eq!(taken_time(&tmpdir.path().join("IMG_2.jpg")), expected);
+        assert_eq!(
+            taken_time(&tmpdir.path().join("IMG_2-edited.jpg")),
+            expected
+        );
+        assert_eq!(taken_time(&tmpdir.path().join("IMG_3.jpg")), None);
+    }
+
+    #[test]
+    fn test_taken_time_numbered_duplicate() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let first = r#"{"photoTakenTime": {"timestamp": "1609459200"}}"#;
+        let second = r#"{"photoTakenTime": {"timestamp": "1612137600"}}"#;
+        std::fs::write(tmpdir.path().join("IMG.jpg.json"), first).unwrap();
+        std::fs::write(tmpdir.path().join("IMG.jpg(1).json"), second).unwrap();
+
+        // The duplicate gets its own sidecar, not the original's
+        assert_eq!(
+            taken_time(&tmpdir.path().join("IMG(1).jpg")),
+            Some(UNIX_EPOCH + Duration::from_secs(1_612_137_600))
+        );
+        assert_eq!(
+            taken_time(&tmpdir.path().join("IMG(1)-edited.jpg")),
+            Some(UNIX_EPOCH + Duration::from_secs(1_612_137_600))
+        );
+        assert_eq!(taken_time(&tmpdir.path().join("IMG(2).jpg")), None);
+    }
+
+    #[test]
+    fn test_taken_time_numbered_name_sidecar() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let sidecar = r#"{"photoTakenTime": {"timestamp": "1612137600"}}"#;
+        std::fs::write(tmpdir.path().join("IMG(1).jpg.json"), sidecar).unwrap();
+        std::fs::write(
+            tmpdir.path().join("PXL.jpg.supplemental-metadata(2).json"),
+            sidecar,
+        )
+        .unwrap();
+
+        let expected = Some(UNIX_EPOCH + Duration::from_secs(1_612_137_600));
+        assert_eq!(taken_time(&tmpdir.path().join("IMG(1).jpg")), expected);
+        assert_eq!(taken_time(&tmpdir.path().join("PXL(2).jpg")), expected);
+        assert_eq!(split_duplicate_number("IMG(a).jpg"), None);
+        assert_eq!(split_duplicate_number("IMG().jpg"), None);
+    }
+}